@@ -1,9 +1,10 @@
+use std::borrow::Cow;
 use std::fmt;
 use std::net;
 use std::net::ToSocketAddrs;
-#[cfg(unix)]
+#[cfg(all(unix, feature = "unix"))]
 use std::os::unix::net as unix;
-#[cfg(unix)]
+#[cfg(all(unix, feature = "unix"))]
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
@@ -19,20 +20,403 @@ use std::str::FromStr;
 /// ```
 /// # use async_stream_connection::Addr;
 /// # fn main() -> Result<(),std::io::Error> {
-/// # #[cfg(unix)]
+/// # #[cfg(all(unix, feature = "unix"))]
 /// let addr: Addr = "/tmp/uds_example".parse()?;
 /// # Ok(())
 /// # }
 /// ```
-/// [`FromStr::parse`] / Deserialize also resolves to the first IP Address if it does not start with `/` or `./`.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// or an abstract socket (linux only):
+/// ```
+/// # use async_stream_connection::Addr;
+/// # fn main() -> Result<(),std::io::Error> {
+/// # #[cfg(all(target_os = "linux", feature = "unix"))]
+/// let addr: Addr = "@my.service".parse()?;
+/// # Ok(())
+/// # }
+/// ```
+/// Abstract socket names are stored in [`Addr::Unix`] with a leading NUL byte, just like tokio expects them.
+///
+/// or a named pipe (windows only):
+/// ```
+/// # use async_stream_connection::Addr;
+/// # fn main() -> Result<(),std::io::Error> {
+/// # #[cfg(windows)]
+/// let addr: Addr = r"\\.\pipe\example".parse()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// or a vsock address (linux only, feature `vsock`):
+/// ```
+/// # use async_stream_connection::Addr;
+/// # fn main() -> Result<(),std::io::Error> {
+/// # #[cfg(all(feature = "vsock", target_os = "linux"))]
+/// let addr: Addr = "vsock://2:1234".parse()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// IPv6 addresses may carry a zone, like `[fe80::1%eth0]:9000`.
+/// It is stored as numeric scope id, so it is displayed as `[fe80::1%2]:9000`.
+///
+/// [`FromStr::parse`] / Deserialize also resolves to the first IP Address if it does not start with `/`, `./`, `@` or `\\.\pipe\`.
+/// Inherited sockets like `fd://3` are no addresses, see [`InheritedFd`](crate::InheritedFd).
+/// Use [`Addr::resolve_all`] to get all of them.
+///
+/// Addresses are ordered by variant first, in declaration order:
+/// all [`Addr::Inet`] addresses come before all Unix sockets, named pipes and vsock addresses.
+/// Within a variant, the inner values are compared,
+/// so IP addresses sort like [`SocketAddr`](net::SocketAddr) and Unix sockets by path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Addr {
     /// An IP socket address
     Inet(net::SocketAddr),
-    #[cfg(unix)]
-    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    #[cfg(all(unix, feature = "unix"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
     ///A UDS address
     Unix(PathBuf),
+    #[cfg(windows)]
+    #[cfg_attr(docsrs, doc(cfg(windows)))]
+    /// A named pipe, like `\\.\pipe\name`
+    Pipe(String),
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "vsock", target_os = "linux"))))]
+    /// A vsock address, like `vsock://cid:port`
+    Vsock {
+        /// Context ID of the VM (`2` is the host)
+        cid: u32,
+        /// Port number
+        port: u32,
+    },
+}
+
+/// The family of an [`Addr`], as returned by [`Addr::family`].
+///
+/// It is displayed as a short lowercase label like `ipv4`, e.g. for metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddrFamily {
+    /// IPv4
+    V4,
+    /// IPv6
+    V6,
+    #[cfg(all(unix, feature = "unix"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+    /// Unix sockets
+    Unix,
+    #[cfg(windows)]
+    #[cfg_attr(docsrs, doc(cfg(windows)))]
+    /// Named pipes
+    Pipe,
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "vsock", target_os = "linux"))))]
+    /// vsock
+    Vsock,
+}
+
+impl fmt::Display for AddrFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AddrFamily::V4 => "ipv4",
+            AddrFamily::V6 => "ipv6",
+            #[cfg(all(unix, feature = "unix"))]
+            AddrFamily::Unix => "unix",
+            #[cfg(windows)]
+            AddrFamily::Pipe => "pipe",
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            AddrFamily::Vsock => "vsock",
+        })
+    }
+}
+
+/// Which IP family [`Addr::from_str_prefer`] picks if a host name has both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum IpPreference {
+    /// Only IPv4 addresses
+    V4,
+    /// Only IPv6 addresses
+    V6,
+    /// The first address, in the order the system resolver returns them
+    #[default]
+    System,
+}
+
+impl Addr {
+    /// Like [`FromStr::parse`], but returns all addresses a host name resolves to, in order.
+    ///
+    /// A Unix socket address is returned as the only entry.
+    /// ```
+    /// # use async_stream_connection::Addr;
+    /// # fn main() -> Result<(),std::io::Error> {
+    /// for addr in Addr::resolve_all("localhost:1337")? {
+    ///     println!("{}", addr);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resolve_all(v: &str) -> std::io::Result<Vec<Addr>> {
+        if let Some(a) = local_from_str(v) {
+            return Ok(vec![a]);
+        }
+        reject_disabled_unix(v)?;
+        if let Some(a) = scoped_from_str(v)? {
+            return Ok(vec![Addr::Inet(a)]);
+        }
+        Ok(v.to_socket_addrs()?.map(Addr::Inet).collect())
+    }
+    /// Resolves anything that implements [`ToSocketAddrs`] into all of its addresses.
+    ///
+    /// Unlike [`Addr::resolve_all`] this never yields a Unix socket address.
+    /// ```
+    /// # use async_stream_connection::Addr;
+    /// # fn main() -> Result<(),std::io::Error> {
+    /// let addrs = Addr::from_socket_addrs(("localhost", 1337))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_socket_addrs<A: ToSocketAddrs>(v: A) -> std::io::Result<Vec<Addr>> {
+        Ok(v.to_socket_addrs()?.map(Addr::Inet).collect())
+    }
+    /// Like [`FromStr::parse`], but picks the first address of the preferred family
+    /// if a host name resolves to several.
+    ///
+    /// If there is no address of that family, an error of kind
+    /// [`std::io::ErrorKind::AddrNotAvailable`] is returned.
+    /// Unix socket addresses are detected the same way and returned regardless of `prefer`.
+    /// ```
+    /// # use async_stream_connection::{Addr, IpPreference};
+    /// # fn main() -> Result<(),std::io::Error> {
+    /// let addr = Addr::from_str_prefer("localhost:1337", IpPreference::V4)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_str_prefer(v: &str, prefer: IpPreference) -> std::io::Result<Addr> {
+        if let Some(a) = local_from_str(v) {
+            return Ok(a);
+        }
+        reject_disabled_unix(v)?;
+        let mut addrs: Box<dyn Iterator<Item = net::SocketAddr>> = match scoped_from_str(v)? {
+            Some(a) => Box::new(std::iter::once(a)),
+            None => Box::new(v.to_socket_addrs()?),
+        };
+        let found = match prefer {
+            IpPreference::V4 => addrs.find(net::SocketAddr::is_ipv4),
+            IpPreference::V6 => addrs.find(net::SocketAddr::is_ipv6),
+            IpPreference::System => addrs.next(),
+        };
+        match found {
+            Some(a) => Ok(Addr::Inet(a)),
+            None => Err(std::io::ErrorKind::AddrNotAvailable.into()),
+        }
+    }
+    /// Like [`FromStr::parse`], but resolves host names without blocking the executor.
+    ///
+    /// Unix socket addresses are detected the same way.
+    /// ```
+    /// # use async_stream_connection::Addr;
+    /// # async fn doc() -> Result<(),std::io::Error> {
+    /// let addr = Addr::resolve("localhost:1337").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resolve(v: &str) -> std::io::Result<Addr> {
+        if let Some(a) = local_from_str(v) {
+            return Ok(a);
+        }
+        reject_disabled_unix(v)?;
+        if let Some(a) = scoped_from_str(v)? {
+            return Ok(Addr::Inet(a));
+        }
+        match tokio::net::lookup_host(v).await?.next() {
+            Some(a) => Ok(Addr::Inet(a)),
+            None => Err(std::io::ErrorKind::AddrNotAvailable.into()),
+        }
+    }
+    /// Returns the family of this address.
+    pub fn family(&self) -> AddrFamily {
+        match self {
+            Addr::Inet(net::SocketAddr::V4(_)) => AddrFamily::V4,
+            Addr::Inet(net::SocketAddr::V6(_)) => AddrFamily::V6,
+            #[cfg(all(unix, feature = "unix"))]
+            Addr::Unix(_) => AddrFamily::Unix,
+            #[cfg(windows)]
+            Addr::Pipe(_) => AddrFamily::Pipe,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Addr::Vsock { .. } => AddrFamily::Vsock,
+        }
+    }
+    /// Returns `true` if this is an IP socket address.
+    pub fn is_inet(&self) -> bool {
+        matches!(self, Addr::Inet(_))
+    }
+    /// Returns `true` if this is a Unix socket address.
+    ///
+    /// Always `false` on platforms without Unix sockets.
+    pub fn is_unix(&self) -> bool {
+        match self {
+            Addr::Inet(_) => false,
+            #[cfg(all(unix, feature = "unix"))]
+            Addr::Unix(_) => true,
+            #[cfg(windows)]
+            Addr::Pipe(_) => false,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Addr::Vsock { .. } => false,
+        }
+    }
+    /// Returns `true` if this address can only be reached from the same machine.
+    ///
+    /// That is a loopback IP (including IPv4-mapped IPv6 ones like `::ffff:127.0.0.1`),
+    /// a Unix socket or a named pipe.
+    /// vsock addresses belong to another VM or its host and are not loopback.
+    pub fn is_loopback(&self) -> bool {
+        match self {
+            Addr::Inet(a) => a.ip().to_canonical().is_loopback(),
+            #[cfg(all(unix, feature = "unix"))]
+            Addr::Unix(_) => true,
+            #[cfg(windows)]
+            Addr::Pipe(_) => true,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Addr::Vsock { .. } => false,
+        }
+    }
+    /// Returns the IP socket address, if this is one.
+    pub fn as_inet(&self) -> Option<&net::SocketAddr> {
+        match self {
+            Addr::Inet(a) => Some(a),
+            #[cfg(all(unix, feature = "unix"))]
+            Addr::Unix(_) => None,
+            #[cfg(windows)]
+            Addr::Pipe(_) => None,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Addr::Vsock { .. } => None,
+        }
+    }
+    /// Returns the port of an IP socket address.
+    ///
+    /// `None` for all other addresses.
+    pub fn port(&self) -> Option<u16> {
+        self.as_inet().map(net::SocketAddr::port)
+    }
+    /// Replaces the port of an IP socket address.
+    ///
+    /// All other addresses are returned unchanged.
+    // there might be no other addresses
+    #[allow(irrefutable_let_patterns)]
+    pub fn with_port(mut self, port: u16) -> Addr {
+        if let Addr::Inet(a) = &mut self {
+            a.set_port(port);
+        }
+        self
+    }
+    /// Parses a URI like `tcp://host:port`, `unix:/path` or `unix://./relative/path`.
+    ///
+    /// Unlike [`FromStr::parse`], the scheme decides the type of the address,
+    /// so a relative path can't be mistaken for a host name.
+    /// Abstract sockets are written as `unix:@name`, named pipes as `npipe:\\.\pipe\name`
+    /// and vsock addresses as `vsock://cid:port`.
+    /// ```
+    /// # use async_stream_connection::Addr;
+    /// # fn main() -> Result<(),std::io::Error> {
+    /// let addr = Addr::from_uri("tcp://127.0.0.1:1337")?;
+    /// assert_eq!(addr.to_uri(), "tcp://127.0.0.1:1337");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_uri(uri: &str) -> std::io::Result<Addr> {
+        let invalid = |what| std::io::Error::new(std::io::ErrorKind::InvalidInput, what);
+        let (scheme, rest) = uri.split_once(':').ok_or_else(|| invalid("missing URI scheme"))?;
+        match scheme {
+            "tcp" => {
+                let rest = rest.strip_prefix("//").ok_or_else(|| invalid("invalid tcp URI"))?;
+                if let Some(a) = scoped_from_str(rest)? {
+                    return Ok(Addr::Inet(a));
+                }
+                match rest.to_socket_addrs()?.next() {
+                    Some(a) => Ok(Addr::Inet(a)),
+                    None => Err(std::io::ErrorKind::AddrNotAvailable.into()),
+                }
+            }
+            #[cfg(all(unix, feature = "unix"))]
+            "unix" => {
+                let path = rest.strip_prefix("//").unwrap_or(rest);
+                #[cfg(any(target_os = "linux", target_os = "android"))]
+                if let Some(name) = path.strip_prefix('@') {
+                    return Ok(Addr::Unix(abstract_path(name.as_bytes())));
+                }
+                if path.is_empty() {
+                    return Err(invalid("empty unix socket path"));
+                }
+                Ok(Addr::Unix(PathBuf::from(path)))
+            }
+            #[cfg(windows)]
+            "npipe" => Ok(Addr::Pipe(rest.to_string())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            "vsock" => local_from_str(uri).ok_or_else(|| invalid("invalid vsock URI")),
+            _ => Err(invalid("unknown URI scheme")),
+        }
+    }
+    /// Formats the address as URI, that [`Addr::from_uri`] parses.
+    pub fn to_uri(&self) -> String {
+        match self {
+            Addr::Inet(a) => format!("tcp://{}", a),
+            #[cfg(all(unix, feature = "unix"))]
+            Addr::Unix(_) => format!("unix:{}", self),
+            #[cfg(windows)]
+            Addr::Pipe(n) => format!("npipe:{}", n),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Addr::Vsock { .. } => self.to_string(),
+        }
+    }
+    /// Same text as [`fmt::Display`], but borrowed where possible.
+    ///
+    /// Unix socket paths that are valid UTF-8 and pipe names are not copied.
+    /// All other addresses have to be formatted and are returned owned.
+    pub fn as_display_str(&self) -> Cow<'_, str> {
+        match self {
+            #[cfg(all(unix, feature = "unix"))]
+            Addr::Unix(p) if !is_abstract(p) => p.to_string_lossy(),
+            #[cfg(windows)]
+            Addr::Pipe(n) => Cow::Borrowed(n),
+            a => Cow::Owned(a.to_string()),
+        }
+    }
+    /// Returns the path of the Unix socket, if this is one.
+    #[cfg(all(unix, feature = "unix"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+    pub fn as_unix(&self) -> Option<&Path> {
+        match self {
+            Addr::Unix(p) => Some(p),
+            _ => None,
+        }
+    }
+    /// Normalizes the path of a Unix socket, so that equal sockets compare (and hash) equal.
+    ///
+    /// `.` components and duplicate slashes are removed and `..` removes the previous component.
+    /// This is purely lexical and does not resolve symlinks, so `link/..` might not be what the OS would use.
+    /// Relative paths are made absolute using the current directory, which is why this can fail.
+    /// Abstract sockets and all other addresses are returned unchanged.
+    pub fn canonicalize(&self) -> std::io::Result<Addr> {
+        match self {
+            #[cfg(all(unix, feature = "unix"))]
+            Addr::Unix(p) if socket_file(p).is_some() => {
+                use std::path::Component;
+                let mut out = PathBuf::new();
+                if p.is_relative() {
+                    out = std::env::current_dir()?;
+                }
+                for c in p.components() {
+                    match c {
+                        Component::CurDir => {}
+                        Component::ParentDir => {
+                            out.pop();
+                        }
+                        c => out.push(c),
+                    }
+                }
+                Ok(Addr::Unix(out))
+            }
+            a => Ok(a.clone()),
+        }
+    }
 }
 
 impl From<net::SocketAddr> for Addr {
@@ -41,58 +425,226 @@ impl From<net::SocketAddr> for Addr {
     }
 }
 
-#[cfg(unix)]
+#[cfg(all(unix, feature = "unix"))]
 impl From<&Path> for Addr {
     fn from(s: &Path) -> Addr {
         Addr::Unix(s.to_path_buf())
     }
 }
-#[cfg(unix)]
+#[cfg(all(unix, feature = "unix"))]
 impl From<PathBuf> for Addr {
     fn from(s: PathBuf) -> Addr {
         Addr::Unix(s)
     }
 }
-#[cfg(unix)]
+#[cfg(all(unix, feature = "unix"))]
 impl From<unix::SocketAddr> for Addr {
     fn from(s: unix::SocketAddr) -> Addr {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            #[cfg(target_os = "android")]
+            use std::os::android::net::SocketAddrExt;
+            #[cfg(target_os = "linux")]
+            use std::os::linux::net::SocketAddrExt;
+            if let Some(name) = s.as_abstract_name() {
+                return Addr::Unix(abstract_path(name));
+            }
+        }
         Addr::Unix(match s.as_pathname() {
             None => Path::new("unnamed").to_path_buf(),
             Some(p) => p.to_path_buf(),
         })
     }
 }
-#[cfg(unix)]
+#[cfg(all(unix, feature = "unix"))]
 impl From<tokio::net::unix::SocketAddr> for Addr {
     fn from(s: tokio::net::unix::SocketAddr) -> Addr {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        if let Some(name) = s.as_abstract_name() {
+            return Addr::Unix(abstract_path(name));
+        }
         Addr::Unix(match s.as_pathname() {
             None => Path::new("unnamed").to_path_buf(),
             Some(p) => p.to_path_buf(),
         })
     }
 }
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+impl From<tokio_vsock::VsockAddr> for Addr {
+    fn from(s: tokio_vsock::VsockAddr) -> Addr {
+        Addr::Vsock {
+            cid: s.cid(),
+            port: s.port(),
+        }
+    }
+}
+/// Abstract socket names are stored as a path with a leading NUL byte (like tokio does)
+#[cfg(all(any(target_os = "linux", target_os = "android"), feature = "unix"))]
+fn abstract_path(name: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    let mut p = Vec::with_capacity(name.len() + 1);
+    p.push(0);
+    p.extend_from_slice(name);
+    PathBuf::from(std::ffi::OsStr::from_bytes(&p))
+}
 impl fmt::Display for Addr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Addr::Inet(n) => n.fmt(f),
-            #[cfg(unix)]
-            Addr::Unix(n) => n.to_string_lossy().fmt(f),
+            #[cfg(all(unix, feature = "unix"))]
+            Addr::Unix(n) => {
+                use std::os::unix::ffi::OsStrExt;
+                match n.as_os_str().as_bytes().split_first() {
+                    Some((0, name)) => write!(f, "@{}", String::from_utf8_lossy(name)),
+                    _ => n.to_string_lossy().fmt(f),
+                }
+            }
+            #[cfg(windows)]
+            Addr::Pipe(n) => n.fmt(f),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Addr::Vsock { cid, port } => write!(f, "vsock://{}:{}", cid, port),
+        }
+    }
+}
+
+/// The socket file at `path`, `None` for abstract sockets
+#[cfg(all(unix, feature = "unix"))]
+pub(crate) fn socket_file(path: &Path) -> Option<PathBuf> {
+    use std::os::unix::ffi::OsStrExt;
+    match path.as_os_str().as_bytes().first() {
+        None | Some(0) => None,
+        Some(_) => Some(path.to_path_buf()),
+    }
+}
+
+/// If `path` names an abstract socket
+#[cfg(all(unix, feature = "unix"))]
+fn is_abstract(path: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().first() == Some(&0)
+}
+
+/// Parses `v` as Unix socket, named pipe or vsock address, if it looks like one
+fn local_from_str(v: &str) -> Option<Addr> {
+    #[cfg(all(unix, feature = "unix"))]
+    if v.starts_with('/') || v.starts_with("./") {
+        return Some(Addr::Unix(PathBuf::from(v)));
+    }
+    #[cfg(all(any(target_os = "linux", target_os = "android"), feature = "unix"))]
+    if let Some(name) = v.strip_prefix('@') {
+        return Some(Addr::Unix(abstract_path(name.as_bytes())));
+    }
+    #[cfg(windows)]
+    if v.starts_with(r"\\.\pipe\") {
+        return Some(Addr::Pipe(v.to_string()));
+    }
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    if let Some((cid, port)) = v.strip_prefix("vsock://").and_then(|a| a.split_once(':')) {
+        if let (Ok(cid), Ok(port)) = (cid.parse(), port.parse()) {
+            return Some(Addr::Vsock { cid, port });
         }
     }
+    let _ = v;
+    None
+}
+
+/// Rejects `v` if it looks like a Unix socket address, but the `unix` feature is off,
+/// so that it is not passed on to the resolver
+fn reject_disabled_unix(v: &str) -> std::io::Result<()> {
+    #[cfg(all(unix, not(feature = "unix")))]
+    if v.starts_with('/') || v.starts_with("./") || v.starts_with('@') {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Unix sockets are disabled",
+        ));
+    }
+    let _ = v;
+    Ok(())
+}
+
+/// Parses a link-local IPv6 address with zone, like `[fe80::1%eth0]:80`.
+///
+/// The zone is stored as numeric scope id, so interface names are looked up.
+fn scoped_from_str(v: &str) -> std::io::Result<Option<net::SocketAddr>> {
+    let (host, port) = match v.strip_prefix('[').and_then(|v| v.split_once("]:")) {
+        Some(hp) => hp,
+        None => return Ok(None),
+    };
+    let (ip, zone) = match host.split_once('%') {
+        Some(iz) => iz,
+        None => return Ok(None),
+    };
+    let invalid = |what| std::io::Error::new(std::io::ErrorKind::InvalidInput, what);
+    let ip: net::Ipv6Addr = ip.parse().map_err(|_| invalid("invalid IPv6 address"))?;
+    let port: u16 = port.parse().map_err(|_| invalid("invalid port"))?;
+    let scope_id = match zone.parse() {
+        Ok(id) => id,
+        #[cfg(unix)]
+        Err(_) => {
+            let name = std::ffi::CString::new(zone).map_err(|_| invalid("invalid zone"))?;
+            match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+                0 => return Err(invalid("unknown network interface")),
+                id => id,
+            }
+        }
+        #[cfg(not(unix))]
+        Err(_) => return Err(invalid("invalid zone")),
+    };
+    Ok(Some(net::SocketAddrV6::new(ip, port, 0, scope_id).into()))
 }
 
 impl FromStr for Addr {
     type Err = std::io::Error;
 
     fn from_str(v: &str) -> Result<Self, Self::Err> {
-        #[cfg(unix)]
-        if v.starts_with('/') || v.starts_with("./") {
-            return Ok(Addr::Unix(PathBuf::from(v)));
-        }
-        match v.to_socket_addrs()?.next() {
-            Some(a) => Ok(Addr::Inet(a)),
-            None => Err(std::io::ErrorKind::AddrNotAvailable.into())
-        }        
+        Addr::from_str_prefer(v, IpPreference::System)
+    }
+}
+
+/// Resolves a host name and port, taking the first address.
+///
+/// An error of kind [`std::io::ErrorKind::AddrNotAvailable`] is returned if nothing was found.
+impl TryFrom<(&str, u16)> for Addr {
+    type Error = std::io::Error;
+
+    fn try_from(v: (&str, u16)) -> Result<Self, Self::Error> {
+        first_socket_addr(v)
+    }
+}
+/// Resolves a host name and port, taking the first address.
+///
+/// An error of kind [`std::io::ErrorKind::AddrNotAvailable`] is returned if nothing was found.
+impl TryFrom<(String, u16)> for Addr {
+    type Error = std::io::Error;
+
+    fn try_from(v: (String, u16)) -> Result<Self, Self::Error> {
+        first_socket_addr(v)
+    }
+}
+/// Same as [`FromStr::from_str`]
+impl TryFrom<&str> for Addr {
+    type Error = std::io::Error;
+
+    fn try_from(v: &str) -> Result<Self, Self::Error> {
+        v.parse()
+    }
+}
+fn first_socket_addr<A: ToSocketAddrs>(v: A) -> std::io::Result<Addr> {
+    match v.to_socket_addrs()?.next() {
+        Some(a) => Ok(Addr::Inet(a)),
+        None => Err(std::io::ErrorKind::AddrNotAvailable.into()),
+    }
+}
+
+/// Serializes to the same string [`fmt::Display`] produces, so that it can be deserialized again
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl serde::ser::Serialize for Addr {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
     }
 }
 
@@ -144,15 +696,279 @@ pub(crate) mod tests {
         }else{
             false
         });
-        #[cfg(unix)]
+        #[cfg(all(unix, feature = "unix"))]
         assert!(if let Ok(Addr::Unix(f)) = Addr::from_str("/path") {
             f == std::path::Path::new("/path")
         }else{
             false
         });
+        #[cfg(all(target_os = "linux", feature = "unix"))]
+        assert!(if let Ok(Addr::Unix(f)) = Addr::from_str("@name") {
+            f == std::path::Path::new("\0name")
+        }else{
+            false
+        });
+    }
+    #[cfg(all(unix, not(feature = "unix")))]
+    #[test]
+    fn unix_disabled() {
+        for p in ["/tmp/sock", "./sock", "@name"] {
+            let e = Addr::from_str(p).unwrap_err();
+            assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+            let e = Addr::resolve_all(p).unwrap_err();
+            assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            let e = rt.block_on(Addr::resolve(p)).unwrap_err();
+            assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+        }
+        assert!(Addr::from_str("127.0.0.1:80").unwrap().is_inet());
+    }
+    #[test]
+    fn scoped_ipv6() {
+        let a = Addr::from_str("[fe80::1%2]:9000").unwrap();
+        match &a {
+            Addr::Inet(net::SocketAddr::V6(f)) => {
+                assert_eq!(f.scope_id(), 2);
+                assert_eq!(f.port(), 9000);
+            }
+            _ => panic!("{:?}", a),
+        }
+        assert_eq!(a.to_string(), "[fe80::1%2]:9000");
+        assert_eq!(Addr::resolve_all("[fe80::1%2]:9000").unwrap(), vec![a]);
+
+        #[cfg(target_os = "linux")]
+        {
+            let a = Addr::from_str("[fe80::1%lo]:9000").unwrap();
+            let id = match a {
+                Addr::Inet(net::SocketAddr::V6(f)) => f.scope_id(),
+                _ => unreachable!(),
+            };
+            assert_ne!(id, 0);
+            assert_eq!(a.to_string(), format!("[fe80::1%{}]:9000", id));
+        }
+        assert!(Addr::from_str("[fe80::1%no-such-if0]:9000").is_err());
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let a = Addr::from_str("127.0.0.1:1234").unwrap();
+        let j = serde_json::to_string(&a).unwrap();
+        assert_eq!(j, "\"127.0.0.1:1234\"");
+        assert_eq!(serde_json::from_str::<Addr>(&j).unwrap(), a);
+
+        let a = Addr::from_str("[::1]:1234").unwrap();
+        let j = serde_json::to_string(&a).unwrap();
+        assert_eq!(serde_json::from_str::<Addr>(&j).unwrap(), a);
+
+        #[cfg(all(unix, feature = "unix"))]
+        {
+            let a = Addr::from_str("/tmp/bla").unwrap();
+            let j = serde_json::to_string(&a).unwrap();
+            assert_eq!(j, "\"/tmp/bla\"");
+            assert_eq!(serde_json::from_str::<Addr>(&j).unwrap(), a);
+        }
+    }
+    #[test]
+    fn resolve_all() {
+        let all = Addr::resolve_all("localhost:0").unwrap();
+        assert!(!all.is_empty());
+        assert!(all.iter().all(|a| a.as_inet().unwrap().port() == 0));
+        #[cfg(all(unix, feature = "unix"))]
+        assert_eq!(
+            Addr::resolve_all("/tmp/bla").unwrap(),
+            vec![Addr::Unix(PathBuf::from("/tmp/bla"))]
+        );
+    }
+    #[test]
+    fn from_str_prefer() {
+        let all = Addr::resolve_all("localhost:80").unwrap();
+        let cases = [
+            (IpPreference::V4, AddrFamily::V4),
+            (IpPreference::V6, AddrFamily::V6),
+        ];
+        for (prefer, want) in cases {
+            match Addr::from_str_prefer("localhost:80", prefer) {
+                Ok(a) => assert_eq!(a.family(), want),
+                // this host has no such address for localhost
+                Err(e) => {
+                    assert_eq!(e.kind(), std::io::ErrorKind::AddrNotAvailable);
+                    assert!(all.iter().all(|a| a.family() != want));
+                }
+            }
+        }
+        assert_eq!(
+            Addr::from_str_prefer("localhost:80", IpPreference::System).unwrap(),
+            all[0]
+        );
+        assert!(Addr::from_str_prefer("127.0.0.1:80", IpPreference::V6).is_err());
+    }
+    #[test]
+    fn resolve() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let a = Addr::resolve("localhost:8080").await.unwrap();
+            assert_eq!(a.as_inet().unwrap().port(), 8080);
+            assert!(a.as_inet().unwrap().ip().is_loopback());
+            assert_eq!(
+                Addr::resolve("127.0.0.1:9000").await.unwrap(),
+                Addr::from_str("127.0.0.1:9000").unwrap()
+            );
+            #[cfg(all(unix, feature = "unix"))]
+            assert_eq!(
+                Addr::resolve("./bla").await.unwrap(),
+                Addr::Unix(PathBuf::from("./bla"))
+            );
+        });
+    }
+    #[test]
+    fn try_from_socket_addrs() {
+        let a = Addr::try_from(("localhost", 8080)).unwrap();
+        assert_eq!(a.port(), Some(8080));
+        assert!(a.is_loopback());
+        assert_eq!(
+            Addr::try_from((String::from("127.0.0.1"), 9000)).unwrap(),
+            Addr::from_str("127.0.0.1:9000").unwrap()
+        );
+        assert_eq!(
+            Addr::try_from("127.0.0.1:9000").unwrap(),
+            Addr::from_str("127.0.0.1:9000").unwrap()
+        );
+        let all = Addr::from_socket_addrs("localhost:8080").unwrap();
+        assert!(all.contains(&a));
+
+        let none: &[net::SocketAddr] = &[];
+        assert_eq!(
+            first_socket_addr(none).unwrap_err().kind(),
+            std::io::ErrorKind::AddrNotAvailable
+        );
+    }
+    #[test]
+    fn as_display_str() {
+        let a: Addr = "127.0.0.1:80".parse().unwrap();
+        assert_eq!(a.as_display_str(), a.to_string());
+        #[cfg(all(unix, feature = "unix"))]
+        {
+            let a = Addr::Unix(PathBuf::from("/tmp/bla"));
+            assert!(matches!(a.as_display_str(), Cow::Borrowed("/tmp/bla")));
+        }
+        #[cfg(all(any(target_os = "linux", target_os = "android"), feature = "unix"))]
+        {
+            let a = Addr::from_str("@bla").unwrap();
+            assert_eq!(a.as_display_str(), "@bla");
+        }
+    }
+    #[test]
+    fn accessors() {
+        let s: net::SocketAddr = "127.0.0.1:1234".parse().unwrap();
+        let a = Addr::Inet(s);
+        assert!(a.is_inet());
+        assert!(!a.is_unix());
+        assert!(a.is_loopback());
+        assert_eq!(a.as_inet(), Some(&s));
+        #[cfg(all(unix, feature = "unix"))]
+        assert_eq!(a.as_unix(), None);
+        assert_eq!(a.port(), Some(1234));
+        assert_eq!(a.with_port(80), "127.0.0.1:80".parse().unwrap());
+        assert!(Addr::Inet("[::ffff:127.0.0.1]:80".parse().unwrap()).is_loopback());
+        assert!(!Addr::Inet("192.168.1.1:80".parse().unwrap()).is_loopback());
+
+        #[cfg(all(unix, feature = "unix"))]
+        {
+            let a = Addr::Unix(PathBuf::from("/tmp/bla"));
+            assert!(!a.is_inet());
+            assert!(a.is_unix());
+            assert!(a.is_loopback());
+            assert_eq!(a.as_inet(), None);
+            assert_eq!(a.as_unix(), Some(Path::new("/tmp/bla")));
+            assert_eq!(a.port(), None);
+            assert_eq!(a.clone().with_port(80), a);
+        }
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn canonicalize() {
+        let a: Addr = "/tmp/./x".parse().unwrap();
+        assert_eq!(a.canonicalize().unwrap(), "/tmp/x".parse().unwrap());
+        let a: Addr = "/tmp//y/../x".parse().unwrap();
+        assert_eq!(a.canonicalize().unwrap(), "/tmp/x".parse().unwrap());
+        let a: Addr = "./x".parse().unwrap();
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(a.canonicalize().unwrap(), Addr::Unix(cwd.join("x")));
+        let a: Addr = "127.0.0.1:80".parse().unwrap();
+        assert_eq!(a.canonicalize().unwrap(), a);
+    }
+    #[test]
+    fn family() {
+        let a: Addr = "127.0.0.1:80".parse().unwrap();
+        assert_eq!(a.family(), AddrFamily::V4);
+        assert_eq!(a.family().to_string(), "ipv4");
+        let a: Addr = "[::1]:80".parse().unwrap();
+        assert_eq!(a.family(), AddrFamily::V6);
+        assert_eq!(a.family().to_string(), "ipv6");
+        #[cfg(all(unix, feature = "unix"))]
+        {
+            let a: Addr = "/tmp/x".parse().unwrap();
+            assert_eq!(a.family(), AddrFamily::Unix);
+            assert_eq!(a.family().to_string(), "unix");
+        }
+    }
+    #[test]
+    fn uri() {
+        fn roundtrip(uri: &str, expected: &str) {
+            let a = Addr::from_uri(uri).unwrap();
+            assert_eq!(a.to_uri(), expected);
+            assert_eq!(Addr::from_uri(expected).unwrap(), a);
+        }
+        roundtrip("tcp://127.0.0.1:80", "tcp://127.0.0.1:80");
+        roundtrip("tcp://[::1]:80", "tcp://[::1]:80");
+        #[cfg(all(unix, feature = "unix"))]
+        {
+            roundtrip("unix:/tmp/sock", "unix:/tmp/sock");
+            roundtrip("unix:///tmp/sock", "unix:/tmp/sock");
+            roundtrip("unix://./rel", "unix:./rel");
+            // no host name lookup
+            assert_eq!(
+                Addr::from_uri("unix:localhost").unwrap(),
+                Addr::Unix(PathBuf::from("localhost"))
+            );
+        }
+        #[cfg(all(target_os = "linux", feature = "unix"))]
+        roundtrip("unix:@name", "unix:@name");
+        #[cfg(windows)]
+        roundtrip(r"npipe:\\.\pipe\name", r"npipe:\\.\pipe\name");
+        #[cfg(all(feature = "vsock", target_os = "linux"))]
+        roundtrip("vsock://3:80", "vsock://3:80");
+        assert!(Addr::from_uri("127.0.0.1:80").is_err());
+        assert!(Addr::from_uri("http://127.0.0.1:80").is_err());
+        assert!(Addr::from_uri("tcp:/tmp/sock").is_err());
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn order() {
+        let mut v: Vec<Addr> = ["/tmp/b", "127.0.0.1:80", "/tmp/a", "[::1]:80", "127.0.0.1:22"]
+            .iter()
+            .map(|a| a.parse().unwrap())
+            .collect();
+        v.sort();
+        let v: Vec<String> = v.iter().map(|a| a.to_string()).collect();
+        assert_eq!(
+            v,
+            ["127.0.0.1:22", "127.0.0.1:80", "[::1]:80", "/tmp/a", "/tmp/b"]
+        );
     }
     #[test]
     fn display() {
+        #[cfg(windows)]
+        assert_eq!(
+            r"\\.\pipe\bla",
+            Addr::from_str(r"\\.\pipe\bla").unwrap().to_string()
+        );
         assert_eq!(
             "127.0.0.1:1234",
             Addr::Inet(net::SocketAddr::V4(net::SocketAddrV4::new(
@@ -161,10 +977,20 @@ pub(crate) mod tests {
             )))
             .to_string()
         );
-        #[cfg(unix)]
+        #[cfg(all(unix, feature = "unix"))]
         assert_eq!(
             "/tmp/bla",
             Addr::Unix(PathBuf::from_str("/tmp/bla").unwrap()).to_string()
         );
+        #[cfg(all(target_os = "linux", feature = "unix"))]
+        assert_eq!(
+            "@my.service",
+            Addr::from_str("@my.service").unwrap().to_string()
+        );
+        #[cfg(all(feature = "vsock", target_os = "linux"))]
+        assert_eq!(
+            "vsock://3:1234",
+            Addr::from_str("vsock://3:1234").unwrap().to_string()
+        );
     }
 }
@@ -0,0 +1,46 @@
+use tokio::io::BufStream;
+
+use crate::Stream;
+
+impl Stream {
+    /// Wraps this stream in a [`BufStream`] with the given read and write buffer sizes.
+    ///
+    /// This saves syscalls for protocols that read and write many small pieces, like lines.
+    /// Written data stays in the buffer until it is full or flushed.
+    /// [`shutdown`](tokio::io::AsyncWriteExt::shutdown) flushes it as well.
+    pub fn buffered(self, read_cap: usize, write_cap: usize) -> BufStream<Stream> {
+        BufStream::with_capacity(read_cap, write_cap, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stream::tests::tcp_pair;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn tiny_writes() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, mut s) = tcp_pair().await.unwrap();
+            let mut c = c.buffered(64, 64);
+            let mut want = Vec::new();
+            for i in 0..200u8 {
+                c.write_u8(i).await.unwrap();
+                want.push(i);
+            }
+            c.flush().await.unwrap();
+            let mut buf = vec![0u8; want.len()];
+            s.read_exact(&mut buf).await.unwrap();
+            assert_eq!(buf, want);
+
+            // shutdown writes what is still buffered
+            c.write_all(b"bye").await.unwrap();
+            c.shutdown().await.unwrap();
+            let mut rest = Vec::new();
+            s.read_to_end(&mut rest).await.unwrap();
+            assert_eq!(rest, b"bye");
+        });
+    }
+}
@@ -0,0 +1,387 @@
+#[cfg(all(unix, feature = "unix"))]
+use socket2::SockAddr;
+use socket2::{Domain, Socket, Type};
+use tokio::net::TcpListener;
+#[cfg(all(unix, feature = "unix"))]
+use tokio::net::UnixListener;
+
+use std::io;
+#[cfg(all(unix, feature = "unix"))]
+use std::path::{Path, PathBuf};
+
+#[cfg(all(unix, feature = "unix"))]
+use crate::addr::socket_file;
+use crate::{Addr, Listener};
+
+/// Configures a [`Listener`] before it is bound.
+///
+/// ```no_run
+/// # use async_stream_connection::ListenerBuilder;
+/// # async fn doc() -> std::io::Result<()> {
+/// let listener = ListenerBuilder::new()
+///     .reuse_address(true)
+///     .bind(&"127.0.0.1:8080".parse()?)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ListenerBuilder {
+    backlog: u32,
+    reuse_address: bool,
+    only_v6: Option<bool>,
+    device: Option<String>,
+    #[cfg(all(
+        unix,
+        not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+    ))]
+    reuse_port: bool,
+    #[cfg(all(unix, feature = "unix"))]
+    unix_mode: Option<u32>,
+}
+
+impl Default for ListenerBuilder {
+    fn default() -> Self {
+        ListenerBuilder {
+            backlog: 1024,
+            // like tokio's TcpListener::bind, which Listener::bind uses
+            reuse_address: cfg!(unix),
+            only_v6: None,
+            device: None,
+            #[cfg(all(
+                unix,
+                not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+            ))]
+            reuse_port: false,
+            #[cfg(all(unix, feature = "unix"))]
+            unix_mode: None,
+        }
+    }
+}
+
+impl ListenerBuilder {
+    /// Creates a new builder with a backlog of 1024 and the same options [`Listener::bind`] uses:
+    /// `SO_REUSEADDR` is on for Unix and all other options are off.
+    pub fn new() -> ListenerBuilder {
+        ListenerBuilder::default()
+    }
+    /// Sets the maximum number of pending connections passed to `listen`.
+    ///
+    /// The OS might silently cap this value (e.g. to `net.core.somaxconn` on Linux).
+    pub fn backlog(mut self, backlog: u32) -> ListenerBuilder {
+        self.backlog = backlog;
+        self
+    }
+    /// Sets `SO_REUSEADDR` on TCP sockets before binding.
+    ///
+    /// This allows a restarted server to bind while old connections are still in `TIME_WAIT`.
+    /// It is on by default for Unix, like for [`Listener::bind`].
+    /// On Windows it would allow another socket to take over the port, so it is off there.
+    pub fn reuse_address(mut self, reuse: bool) -> ListenerBuilder {
+        self.reuse_address = reuse;
+        self
+    }
+    /// Sets `IPV6_V6ONLY` on IPv6 TCP sockets before binding.
+    ///
+    /// If set, a listener on `[::]` only accepts IPv6 connections.
+    /// Otherwise it accepts IPv4 connections as well, as IPv4-mapped addresses.
+    /// If not called, the OS default is kept (e.g. `net.ipv6.bindv6only` on Linux).
+    /// IPv4 and Unix listeners ignore this option.
+    pub fn only_v6(mut self, only_v6: bool) -> ListenerBuilder {
+        self.only_v6 = Some(only_v6);
+        self
+    }
+    /// Binds TCP sockets to the network interface `ifname` (like `eth0`), using `SO_BINDTODEVICE`.
+    ///
+    /// The listener then only accepts connections that arrive on this interface.
+    /// This needs `CAP_NET_RAW` and is only available on Linux.
+    /// Elsewhere binding fails with an error of kind [`io::ErrorKind::Unsupported`].
+    /// Unix listeners ignore this option.
+    pub fn bind_device(mut self, ifname: &str) -> ListenerBuilder {
+        self.device = Some(ifname.to_string());
+        self
+    }
+    /// Sets `SO_REUSEPORT` on TCP sockets before binding.
+    ///
+    /// This allows multiple sockets to listen on the same port, with the OS distributing incoming connections.
+    #[cfg(all(
+        unix,
+        not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+    ))]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub fn reuse_port(mut self, reuse: bool) -> ListenerBuilder {
+        self.reuse_port = reuse;
+        self
+    }
+    /// Sets the permissions of the socket file of a Unix listener, like `0o600`.
+    ///
+    /// The umask is narrowed while binding, so that the file never has more permissions than `mode`.
+    /// Note that the umask is process wide: files created by other threads at the same time are affected as well.
+    /// Abstract sockets have no file and ignore this option.
+    #[cfg(all(unix, feature = "unix"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+    pub fn unix_mode(mut self, mode: u32) -> ListenerBuilder {
+        self.unix_mode = Some(mode);
+        self
+    }
+    /// Creates a new Listener with the configured options, which will be bound to the specified address.
+    ///
+    /// The returned listener is ready for accepting connections.
+    /// Named pipes and vsock sockets don't have any of these options and are created like [`Listener::bind`] does.
+    pub async fn bind(&self, s: &Addr) -> io::Result<Listener> {
+        match s {
+            Addr::Inet(s) => {
+                let socket = Socket::new(Domain::for_address(*s), Type::STREAM, None)?;
+                socket.set_reuse_address(self.reuse_address)?;
+                if let (Some(only_v6), true) = (self.only_v6, s.is_ipv6()) {
+                    socket.set_only_v6(only_v6)?;
+                }
+                #[cfg(target_os = "linux")]
+                if let Some(device) = &self.device {
+                    socket.bind_device(Some(device.as_bytes()))?;
+                }
+                #[cfg(not(target_os = "linux"))]
+                if self.device.is_some() {
+                    return Err(crate::unsupported("SO_BINDTODEVICE", "Listener::Inet").into());
+                }
+                #[cfg(all(
+                    unix,
+                    not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+                ))]
+                if self.reuse_port {
+                    socket.set_reuse_port(true)?;
+                }
+                socket.set_nonblocking(true)?;
+                socket.bind(&(*s).into())?;
+                socket.listen(self.backlog.min(i32::MAX as u32) as i32)?;
+                TcpListener::from_std(socket.into()).map(Listener::Inet)
+            }
+            #[cfg(all(unix, feature = "unix"))]
+            Addr::Unix(s) => self.bind_unix(s).map(|l| Listener::Unix(l, socket_file(s), true)),
+            #[cfg(windows)]
+            Addr::Pipe(_) => Listener::bind(s).await,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Addr::Vsock { .. } => Listener::bind(s).await,
+        }
+    }
+    /// Binds a Unix listener to a temporary path next to `final_path` and renames it onto `final_path`.
+    ///
+    /// This replaces an existing socket file atomically, so that clients never see it missing.
+    /// The temporary path is `final_path` with `.tmp` appended. A leftover file there,
+    /// e.g. from a crash during a previous call, is removed first.
+    /// The listener removes `final_path` on drop, and [`Listener::local_addr`] returns it,
+    /// although the OS still knows the socket by its temporary path.
+    ///
+    /// A server still listening on the replaced file should turn off
+    /// [`Listener::set_unlink_on_drop`], or it removes the new file when it exits.
+    /// Abstract sockets return an error of kind [`io::ErrorKind::InvalidInput`].
+    #[cfg(all(unix, feature = "unix"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+    pub fn bind_unix_atomic(&self, final_path: &Path) -> io::Result<Listener> {
+        let final_path = socket_file(final_path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "abstract sockets have no file")
+        })?;
+        let mut tmp = final_path.clone().into_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        match std::fs::remove_file(&tmp) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        let l = self.bind_unix(&tmp)?;
+        if let Err(e) = std::fs::rename(&tmp, &final_path) {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+        Ok(Listener::Unix(l, Some(final_path), true))
+    }
+    /// Creates a listening Unix socket at `path` with the configured options
+    #[cfg(all(unix, feature = "unix"))]
+    fn bind_unix(&self, path: &Path) -> io::Result<UnixListener> {
+        let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+        socket.set_nonblocking(true)?;
+        match self.unix_mode {
+            Some(mode) => bind_with_mode(&socket, path, mode)?,
+            None => socket.bind(&SockAddr::unix(path)?)?,
+        }
+        socket.listen(self.backlog.min(i32::MAX as u32) as i32)?;
+        UnixListener::from_std(socket.into())
+    }
+}
+
+/// Binds `socket` to `path`, creating the socket file with exactly `mode`
+#[cfg(all(unix, feature = "unix"))]
+fn bind_with_mode(socket: &Socket, path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let addr = SockAddr::unix(path)?;
+    // bind creates the file with all permissions not masked out
+    let old = unsafe { libc::umask(!mode as libc::mode_t & 0o777) };
+    let r = socket.bind(&addr);
+    unsafe { libc::umask(old) };
+    r?;
+    if socket_file(path).is_none() {
+        // abstract socket
+        return Ok(());
+    }
+    // the umask can only take permissions away
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(target_os = "linux")]
+    use crate::Stream;
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn backlog() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = ListenerBuilder::new()
+                .backlog(16)
+                .bind(&"127.0.0.1:0".parse().unwrap())
+                .await
+                .unwrap();
+            let a = l.local_addr().unwrap();
+            let c = crate::Stream::connect(&a).await.unwrap();
+            let (s, peer) = l.accept().await.unwrap();
+            assert_eq!(peer, c.local_addr().unwrap());
+            assert_eq!(s.peer_addr().unwrap(), c.local_addr().unwrap());
+        });
+    }
+    #[test]
+    // without Unix sockets, there are only TCP listeners
+    #[allow(irrefutable_let_patterns)]
+    fn reuse_address() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = ListenerBuilder::new()
+                .reuse_address(true)
+                .bind(&"127.0.0.1:0".parse().unwrap())
+                .await
+                .unwrap();
+            if let Listener::Inet(l) = &l {
+                let s = socket2::SockRef::from(l);
+                assert!(s.reuse_address().unwrap());
+            } else {
+                unreachable!()
+            }
+            // the default matches Listener::bind
+            let a: crate::Addr = "127.0.0.1:0".parse().unwrap();
+            let l = ListenerBuilder::new().bind(&a).await.unwrap();
+            let plain = Listener::bind(&a).await.unwrap();
+            if let (Listener::Inet(l), Listener::Inet(plain)) = (&l, &plain) {
+                assert_eq!(
+                    socket2::SockRef::from(l).reuse_address().unwrap(),
+                    socket2::SockRef::from(plain).reuse_address().unwrap()
+                );
+            } else {
+                unreachable!()
+            }
+        });
+    }
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn bind_device() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = match ListenerBuilder::new()
+                .bind_device("lo")
+                .bind(&"127.0.0.1:0".parse().unwrap())
+                .await
+            {
+                Ok(l) => l,
+                // needs CAP_NET_RAW
+                Err(e) if e.kind() == io::ErrorKind::PermissionDenied => return,
+                Err(e) => panic!("{}", e),
+            };
+            let a = l.local_addr().unwrap();
+            let (c, s) = tokio::join!(Stream::connect_device(&a, "lo"), l.accept());
+            c.unwrap();
+            s.unwrap();
+        });
+    }
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn only_v6() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = ListenerBuilder::new()
+                .only_v6(true)
+                .bind(&"[::]:0".parse().unwrap())
+                .await
+                .unwrap();
+            let port = l.local_addr().unwrap().as_inet().unwrap().port();
+            let v4: Addr = format!("127.0.0.1:{}", port).parse().unwrap();
+            let e = Stream::connect(&v4).await.unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused);
+            let v6: Addr = format!("[::1]:{}", port).parse().unwrap();
+            let (c, s) = tokio::join!(Stream::connect(&v6), l.accept());
+            c.unwrap();
+            s.unwrap();
+        });
+    }
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reuse_port() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let b = ListenerBuilder::new().reuse_port(true);
+            let first = b.bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            let a = first.local_addr().unwrap();
+            let _second = b.bind(&a).await.expect("second bind failed");
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_bind() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let _ = std::fs::remove_file("/tmp/asc_builder.sock");
+            let a: Addr = "/tmp/asc_builder.sock".parse().unwrap();
+            let l = ListenerBuilder::new().bind(&a).await.unwrap();
+            let c = crate::Stream::connect(&a).await.unwrap();
+            let (s, _) = l.accept().await.unwrap();
+            assert_eq!(c.peer_addr().unwrap(), s.local_addr().unwrap());
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let _ = std::fs::remove_file("/tmp/asc_mode.sock");
+            let a: Addr = "/tmp/asc_mode.sock".parse().unwrap();
+            let _l = ListenerBuilder::new().unix_mode(0o600).bind(&a).await.unwrap();
+            let m = std::fs::metadata("/tmp/asc_mode.sock").unwrap().permissions().mode();
+            assert_eq!(m & 0o777, 0o600);
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_atomic() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let path = Path::new("/tmp/asc_atomic.sock");
+            let _ = std::fs::remove_file(path);
+            let mut old = Listener::bind(&Addr::Unix(path.to_path_buf())).await.unwrap();
+            old.set_unlink_on_drop(false);
+            // leftover of a crashed attempt
+            std::fs::write("/tmp/asc_atomic.sock.tmp", b"").unwrap();
+
+            let l = ListenerBuilder::new().bind_unix_atomic(path).unwrap();
+            assert!(!Path::new("/tmp/asc_atomic.sock.tmp").exists());
+            assert_eq!(l.local_addr().unwrap(), Addr::Unix(path.to_path_buf()));
+            let (c, s) = tokio::join!(crate::Stream::connect_to_listener(&l), l.accept());
+            c.unwrap();
+            s.unwrap();
+            drop(old);
+            assert!(path.exists());
+            drop(l);
+            assert!(!path.exists());
+        });
+    }
+}
@@ -0,0 +1,154 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::OnceLock;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, Error, ReadBuf};
+
+use crate::{Addr, Stream};
+
+/// A [`Stream`] that looks up its addresses only once,
+/// created by [`Stream::cache_addrs`] or [`Stream::connect_cached`].
+///
+/// [`Stream::peer_addr`] and [`Stream::local_addr`] ask the OS on every call.
+/// The addresses of a connected socket don't change, so they can be cached
+/// for servers that log them on many events.
+/// ```
+/// # use async_stream_connection::Stream;
+/// # async fn doc(s: Stream) -> std::io::Result<()> {
+/// let s = s.cache_addrs();
+/// println!("{} connected", s.cached_peer_addr()?);
+/// println!("{} still connected", s.cached_peer_addr()?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CachedAddrStream {
+    inner: Stream,
+    peer: OnceLock<Addr>,
+    local: OnceLock<Addr>,
+}
+
+impl Stream {
+    /// Wraps this stream to cache its addresses on first use.
+    pub fn cache_addrs(self) -> CachedAddrStream {
+        CachedAddrStream {
+            inner: self,
+            peer: OnceLock::new(),
+            local: OnceLock::new(),
+        }
+    }
+    /// Opens a connection like [`Stream::connect`] and looks up both of its addresses right away.
+    ///
+    /// As both addresses are stored already, [`CachedAddrStream::cached_peer_addr`] and
+    /// [`CachedAddrStream::cached_local_addr`] never fail on the returned stream.
+    pub async fn connect_cached(addr: &Addr) -> io::Result<CachedAddrStream> {
+        let s = Stream::connect(addr).await?;
+        Ok(CachedAddrStream {
+            peer: OnceLock::from(s.peer_addr()?),
+            local: OnceLock::from(s.local_addr()?),
+            inner: s,
+        })
+    }
+}
+
+impl CachedAddrStream {
+    /// Returns the remote address, asking the OS only on the first call.
+    pub fn cached_peer_addr(&self) -> io::Result<&Addr> {
+        cached(&self.peer, || self.inner.peer_addr())
+    }
+    /// Returns the local address, asking the OS only on the first call.
+    pub fn cached_local_addr(&self) -> io::Result<&Addr> {
+        cached(&self.local, || self.inner.local_addr())
+    }
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &Stream {
+        &self.inner
+    }
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> Stream {
+        self.inner
+    }
+}
+
+/// Errors are not cached
+fn cached(cell: &OnceLock<Addr>, f: impl FnOnce() -> io::Result<Addr>) -> io::Result<&Addr> {
+    if let Some(a) = cell.get() {
+        return Ok(a);
+    }
+    let a = f()?;
+    Ok(cell.get_or_init(|| a))
+}
+
+impl AsyncRead for CachedAddrStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+impl AsyncWrite for CachedAddrStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<Result<usize, Error>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stream::tests::{local_socket_pair, tcp_pair};
+    use crate::{Addr, Stream};
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn peer_addr_once() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = tcp_pair().await.unwrap();
+            let c = c.cache_addrs();
+            let a = c.cached_peer_addr().unwrap();
+            let b = c.cached_peer_addr().unwrap();
+            assert_eq!(a, &s.local_addr().unwrap());
+            // the second call returned the stored address instead of asking again
+            assert!(std::ptr::eq(a, b));
+            assert_eq!(c.cached_local_addr().unwrap(), &s.peer_addr().unwrap());
+        });
+    }
+    #[test]
+    fn connect_cached() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (l, a) = local_socket_pair().await.unwrap();
+            let (c, s) = tokio::join!(Stream::connect_cached(&a), l.accept());
+            let (c, (_, peer)) = (c.unwrap(), s.unwrap());
+            assert_eq!(c.cached_peer_addr().unwrap(), &a);
+            assert_eq!(c.cached_peer_addr().unwrap(), &c.get_ref().peer_addr().unwrap());
+            assert_eq!(c.cached_local_addr().unwrap(), &Addr::from(peer));
+            assert_eq!(c.cached_local_addr().unwrap(), &c.get_ref().local_addr().unwrap());
+        });
+    }
+}
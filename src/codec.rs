@@ -0,0 +1,39 @@
+use tokio_util::codec::{Decoder, Framed};
+
+use crate::Stream;
+
+impl Stream {
+    /// Turns this stream into a [`Framed`] sink and stream of frames,
+    /// using `codec` to encode and decode them.
+    ///
+    /// ```
+    /// # use async_stream_connection::Stream;
+    /// use async_stream_connection::tokio_util::codec::LinesCodec;
+    /// # async fn doc(s: Stream) {
+    /// let lines = s.framed(LinesCodec::new());
+    /// # }
+    /// ```
+    pub fn framed<C: Decoder>(self, codec: C) -> Framed<Stream, C> {
+        Framed::new(self, codec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stream::tests::tcp_pair;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::runtime::Builder;
+    use tokio_util::codec::LinesCodec;
+
+    #[test]
+    fn lines() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = tcp_pair().await.unwrap();
+            let mut c = c.framed(LinesCodec::new());
+            let mut s = s.framed(LinesCodec::new());
+            c.send("hello").await.unwrap();
+            assert_eq!(s.next().await.unwrap().unwrap(), "hello");
+        });
+    }
+}
@@ -0,0 +1,133 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, Error, ReadBuf};
+
+/// Counts the bytes read from and written to the wrapped stream.
+///
+/// Works with any [`AsyncRead`] + [`AsyncWrite`], like a [`Stream`](crate::Stream) or a TLS stream.
+/// ```
+/// # use async_stream_connection::{CountingStream, Stream};
+/// # async fn doc(s: Stream) {
+/// let s = CountingStream::new(s);
+/// // ... use it
+/// println!("{} bytes in, {} bytes out", s.bytes_read(), s.bytes_written());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CountingStream<S> {
+    inner: S,
+    read: AtomicU64,
+    written: AtomicU64,
+}
+
+impl<S> CountingStream<S> {
+    /// Wraps `inner` with both counters at zero.
+    pub fn new(inner: S) -> CountingStream<S> {
+        CountingStream {
+            inner,
+            read: AtomicU64::new(0),
+            written: AtomicU64::new(0),
+        }
+    }
+    /// Number of bytes read so far
+    pub fn bytes_read(&self) -> u64 {
+        self.read.load(Ordering::Relaxed)
+    }
+    /// Number of bytes written so far
+    pub fn bytes_written(&self) -> u64 {
+        self.written.load(Ordering::Relaxed)
+    }
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+    /// Gets a mutable reference to the underlying stream.
+    ///
+    /// I/O on it directly is not counted.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for CountingStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        let before = buf.filled().len();
+        let r = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = r {
+            let n = buf.filled().len() - before;
+            self.read.fetch_add(n as u64, Ordering::Relaxed);
+        }
+        r
+    }
+}
+impl<S: AsyncWrite + Unpin> AsyncWrite for CountingStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        let r = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = r {
+            self.written.fetch_add(n as u64, Ordering::Relaxed);
+        }
+        r
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<Result<usize, Error>> {
+        let r = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = r {
+            self.written.fetch_add(n as u64, Ordering::Relaxed);
+        }
+        r
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::tests::tcp_pair;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn count_100() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, mut s) = tcp_pair().await.unwrap();
+            let mut c = CountingStream::new(c);
+            c.write_all(&[1; 100]).await.unwrap();
+            let mut buf = [0u8; 100];
+            s.read_exact(&mut buf).await.unwrap();
+            s.write_all(&buf).await.unwrap();
+            c.read_exact(&mut buf).await.unwrap();
+            assert_eq!(c.bytes_written(), 100);
+            assert_eq!(c.bytes_read(), 100);
+        });
+    }
+}
@@ -0,0 +1,187 @@
+use tokio::net::UdpSocket;
+#[cfg(all(unix, feature = "unix"))]
+use tokio::net::UnixDatagram;
+
+use std::io;
+
+use crate::Addr;
+
+/// A datagram socket, either UDP or Unix.
+///
+/// Unlike [`Listener`](crate::Listener), a Unix datagram socket does not remove its socket file when dropped.
+#[derive(Debug)]
+pub enum Datagram {
+    /// A UDP socket
+    Inet(UdpSocket),
+    #[cfg(all(unix, feature = "unix"))]
+    /// A Unix datagram socket
+    Unix(UnixDatagram),
+}
+
+impl From<UdpSocket> for Datagram {
+    fn from(s: UdpSocket) -> Datagram {
+        Datagram::Inet(s)
+    }
+}
+
+#[cfg(all(unix, feature = "unix"))]
+impl From<UnixDatagram> for Datagram {
+    fn from(s: UnixDatagram) -> Datagram {
+        Datagram::Unix(s)
+    }
+}
+
+#[cfg(any(unix, windows))]
+fn family_mismatch() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "address family does not match the socket",
+    )
+}
+
+impl Datagram {
+    /// Creates a new datagram socket, which will be bound to the specified address.
+    pub async fn bind(s: &Addr) -> io::Result<Datagram> {
+        match s {
+            Addr::Inet(s) => UdpSocket::bind(s).await.map(Datagram::Inet),
+            #[cfg(all(unix, feature = "unix"))]
+            Addr::Unix(s) => UnixDatagram::bind(s).map(Datagram::Unix),
+            #[cfg(windows)]
+            Addr::Pipe(_) => Err(family_mismatch()),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Addr::Vsock { .. } => Err(family_mismatch()),
+        }
+    }
+
+    /// Connects the socket to a remote address.
+    ///
+    /// [`Datagram::send`] and [`Datagram::recv`] then talk to this address only.
+    /// The address must be of the same family as the socket.
+    pub async fn connect(&self, s: &Addr) -> io::Result<()> {
+        match (self, s) {
+            (Datagram::Inet(d), Addr::Inet(s)) => d.connect(s).await,
+            #[cfg(all(unix, feature = "unix"))]
+            (Datagram::Unix(d), Addr::Unix(s)) => d.connect(s),
+            #[cfg(any(unix, windows))]
+            _ => Err(family_mismatch()),
+        }
+    }
+
+    /// Sends data on the socket to the connected remote address.
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Datagram::Inet(d) => d.send(buf).await,
+            #[cfg(all(unix, feature = "unix"))]
+            Datagram::Unix(d) => d.send(buf).await,
+        }
+    }
+
+    /// Receives a single datagram from the connected remote address.
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Datagram::Inet(d) => d.recv(buf).await,
+            #[cfg(all(unix, feature = "unix"))]
+            Datagram::Unix(d) => d.recv(buf).await,
+        }
+    }
+
+    /// Sends data on the socket to the given address.
+    ///
+    /// The address must be of the same family as the socket.
+    pub async fn send_to(&self, buf: &[u8], target: &Addr) -> io::Result<usize> {
+        match (self, target) {
+            (Datagram::Inet(d), Addr::Inet(t)) => d.send_to(buf, t).await,
+            #[cfg(all(unix, feature = "unix"))]
+            (Datagram::Unix(d), Addr::Unix(t)) => d.send_to(buf, t).await,
+            #[cfg(any(unix, windows))]
+            _ => Err(family_mismatch()),
+        }
+    }
+
+    /// Receives a single datagram and the address it came from.
+    pub async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, Addr)> {
+        match self {
+            Datagram::Inet(d) => d.recv_from(buf).await.map(|(n, a)| (n, Addr::Inet(a))),
+            #[cfg(all(unix, feature = "unix"))]
+            Datagram::Unix(d) => d.recv_from(buf).await.map(|(n, a)| (n, Addr::from(a))),
+        }
+    }
+
+    /// Returns the local address that this socket is bound to.
+    pub fn local_addr(&self) -> io::Result<Addr> {
+        match self {
+            Datagram::Inet(d) => d.local_addr().map(Addr::Inet),
+            #[cfg(all(unix, feature = "unix"))]
+            Datagram::Unix(d) => d.local_addr().map(|e| e.into()),
+        }
+    }
+
+    /// Returns the address of the connected remote peer.
+    pub fn peer_addr(&self) -> io::Result<Addr> {
+        match self {
+            Datagram::Inet(d) => d.peer_addr().map(Addr::Inet),
+            #[cfg(all(unix, feature = "unix"))]
+            Datagram::Unix(d) => d.peer_addr().map(|e| e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::runtime::Builder;
+
+    async fn echo(server: Datagram) {
+        let mut buf = [0u8; 32];
+        let (n, from) = server.recv_from(&mut buf).await.unwrap();
+        server.send_to(&buf[..n], &from).await.unwrap();
+    }
+
+    #[test]
+    fn udp_echo() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let server = Datagram::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            let a = server.local_addr().unwrap();
+            tokio::spawn(echo(server));
+
+            let client = Datagram::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            client.connect(&a).await.unwrap();
+            assert_eq!(client.peer_addr().unwrap(), a);
+            client.send(b"1234").await.unwrap();
+            let mut buf = [0u8; 32];
+            let n = client.recv(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"1234");
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_echo() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let _ = std::fs::remove_file("/tmp/asc_dgram_server.sock");
+            let _ = std::fs::remove_file("/tmp/asc_dgram_client.sock");
+            let a: Addr = "/tmp/asc_dgram_server.sock".parse().unwrap();
+            let server = Datagram::bind(&a).await.unwrap();
+            tokio::spawn(echo(server));
+
+            let client = Datagram::bind(&"/tmp/asc_dgram_client.sock".parse().unwrap())
+                .await
+                .unwrap();
+            client.send_to(b"1234", &a).await.unwrap();
+            let mut buf = [0u8; 32];
+            let (n, from) = client.recv_from(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"1234");
+            assert_eq!(from, a);
+
+            let e = client
+                .send_to(b"1234", &"127.0.0.1:1".parse().unwrap())
+                .await
+                .unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+
+            std::fs::remove_file("/tmp/asc_dgram_server.sock").unwrap();
+            std::fs::remove_file("/tmp/asc_dgram_client.sock").unwrap();
+        });
+    }
+}
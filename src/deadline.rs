@@ -0,0 +1,144 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, Error, ReadBuf};
+use tokio::time::{Instant, Sleep};
+
+use crate::Stream;
+
+/// Fails all reads and writes on the wrapped stream once a deadline has passed.
+///
+/// Unlike [`TimeoutStream`](crate::TimeoutStream), activity does not extend the deadline:
+/// it caps the lifetime of the whole connection.
+/// After the deadline, reads and writes return an error of kind [`io::ErrorKind::TimedOut`].
+/// Flushing and shutting down still reach the wrapped stream.
+/// ```
+/// # use async_stream_connection::Stream;
+/// # use std::time::Duration;
+/// # use tokio::time::Instant;
+/// # async fn doc(s: Stream) {
+/// let s = s.with_deadline(Instant::now() + Duration::from_secs(300));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DeadlineStream<S> {
+    inner: S,
+    sleep: Pin<Box<Sleep>>,
+}
+
+impl Stream {
+    /// Wraps this stream, so that it can't be used after `at`.
+    pub fn with_deadline(self, at: Instant) -> DeadlineStream<Stream> {
+        DeadlineStream::new(self, at)
+    }
+}
+
+impl<S> DeadlineStream<S> {
+    /// Wraps `inner`, failing its I/O after `at`.
+    pub fn new(inner: S, at: Instant) -> DeadlineStream<S> {
+        DeadlineStream {
+            inner,
+            sleep: Box::pin(tokio::time::sleep_until(at)),
+        }
+    }
+    /// The deadline
+    pub fn deadline(&self) -> Instant {
+        self.sleep.deadline()
+    }
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+    /// Gets a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+    /// Errors once the deadline passed, registers `cx` for it otherwise
+    fn check(&mut self, cx: &mut Context) -> io::Result<()> {
+        match self.sleep.as_mut().poll(cx) {
+            Poll::Ready(()) => Err(io::ErrorKind::TimedOut.into()),
+            Poll::Pending => Ok(()),
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for DeadlineStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        self.check(cx)?;
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+impl<S: AsyncWrite + Unpin> AsyncWrite for DeadlineStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        self.check(cx)?;
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<Result<usize, Error>> {
+        self.check(cx)?;
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::tests::tcp_pair;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn busy_peer() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, mut s) = tcp_pair().await.unwrap();
+            let start = Instant::now();
+            let mut c = c.with_deadline(start + Duration::from_millis(50));
+            let sender = tokio::spawn(async move {
+                while s.write_all(&[0; 64]).await.is_ok() {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            });
+            let mut buf = [0u8; 64];
+            let e = loop {
+                if let Err(e) = c.read(&mut buf).await {
+                    break e;
+                }
+            };
+            assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+            assert!(start.elapsed() >= Duration::from_millis(50));
+            drop(c);
+            sender.await.unwrap();
+        });
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+use std::io;
+
+/// Errors of operations that are not available on every kind of socket.
+///
+/// It converts into an [`io::Error`], so `?` works in functions returning [`io::Result`].
+/// Operations that mirror tokio's API return [`io::Error`]s directly.
+/// For unsupported operations, their inner error is an [`Error::Unsupported`].
+#[derive(Debug)]
+pub enum Error {
+    /// An error of the underlying socket
+    Io(io::Error),
+    /// The operation does not exist on this type of socket
+    Unsupported {
+        /// The operation or socket option, like `TCP_NODELAY`
+        op: &'static str,
+        /// The variant of the socket, like `Stream::Unix`
+        variant: &'static str,
+    },
+}
+
+/// Result of operations that are not available on every kind of socket
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// The corresponding [`io::ErrorKind`], [`io::ErrorKind::Unsupported`] for [`Error::Unsupported`]
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Error::Io(e) => e.kind(),
+            Error::Unsupported { .. } => io::ErrorKind::Unsupported,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => e.fmt(f),
+            Error::Unsupported { op, variant } => write!(f, "{} is not supported by {}", op, variant),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Unsupported { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> io::Error {
+        match e {
+            Error::Io(e) => e,
+            e @ Error::Unsupported { .. } => io::Error::new(io::ErrorKind::Unsupported, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_message() {
+        let e = Error::Unsupported {
+            op: "TCP_NODELAY",
+            variant: "Stream::Unix",
+        };
+        assert_eq!(e.kind(), io::ErrorKind::Unsupported);
+        assert_eq!(e.to_string(), "TCP_NODELAY is not supported by Stream::Unix");
+        let e = io::Error::from(e);
+        assert_eq!(e.kind(), io::ErrorKind::Unsupported);
+        assert_eq!(e.to_string(), "TCP_NODELAY is not supported by Stream::Unix");
+        assert!(e.get_ref().unwrap().is::<Error>());
+    }
+}
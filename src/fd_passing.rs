@@ -0,0 +1,172 @@
+use std::io::{self, IoSlice, IoSliceMut};
+use std::mem;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::ptr;
+use tokio::io::Interest;
+
+use crate::{unsupported, Stream};
+
+/// Maximum number of file descriptors received at once (`SCM_MAX_FD` on Linux)
+const MAX_FDS: usize = 253;
+
+/// Control message buffer, aligned for `cmsghdr`
+fn cmsg_buffer(fds: usize) -> Vec<libc::cmsghdr> {
+    let space = unsafe { libc::CMSG_SPACE((fds * mem::size_of::<RawFd>()) as _) } as usize;
+    let hdr = mem::size_of::<libc::cmsghdr>();
+    // SAFETY: cmsghdr is plain old data, all zero is a valid value
+    vec![unsafe { mem::zeroed() }; space.div_ceil(hdr)]
+}
+
+impl Stream {
+    /// Sends `bufs` along with the file descriptors `fds` (`SCM_RIGHTS`).
+    ///
+    /// The descriptors stay open in this process.
+    /// At least one byte of data has to be sent with them.
+    /// Returns the number of bytes written.
+    ///
+    /// TCP sockets can't carry file descriptors and return [`Error::Unsupported`](crate::Error::Unsupported).
+    pub async fn send_fds(&self, bufs: &[IoSlice<'_>], fds: &[RawFd]) -> crate::Result<usize> {
+        let s = match self {
+            Stream::Unix(s) => s,
+            _ => return Err(unsupported("SCM_RIGHTS", self.variant())),
+        };
+        let mut cmsg = cmsg_buffer(fds.len());
+        s.async_io(Interest::WRITABLE, || {
+            // SAFETY: all pointers point to live buffers of the given length
+            unsafe {
+                let mut msg: libc::msghdr = mem::zeroed();
+                msg.msg_iov = bufs.as_ptr() as *mut libc::iovec;
+                msg.msg_iovlen = bufs.len() as _;
+                if !fds.is_empty() {
+                    let data_len = mem::size_of_val(fds);
+                    msg.msg_control = cmsg.as_mut_ptr().cast();
+                    msg.msg_controllen = libc::CMSG_SPACE(data_len as _) as _;
+                    let hdr = libc::CMSG_FIRSTHDR(&msg);
+                    (*hdr).cmsg_level = libc::SOL_SOCKET;
+                    (*hdr).cmsg_type = libc::SCM_RIGHTS;
+                    (*hdr).cmsg_len = libc::CMSG_LEN(data_len as _) as _;
+                    ptr::copy_nonoverlapping(
+                        fds.as_ptr().cast::<u8>(),
+                        libc::CMSG_DATA(hdr),
+                        data_len,
+                    );
+                }
+                match libc::sendmsg(s.as_raw_fd(), &msg, 0) {
+                    -1 => Err(io::Error::last_os_error()),
+                    n => Ok(n as usize),
+                }
+            }
+        })
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Receives data into `bufs` and appends passed file descriptors (`SCM_RIGHTS`) to `fds`.
+    ///
+    /// The caller owns the received descriptors and is responsible for closing them.
+    /// Returns the number of bytes read.
+    ///
+    /// TCP sockets can't carry file descriptors and return [`Error::Unsupported`](crate::Error::Unsupported).
+    pub async fn recv_fds(
+        &self,
+        bufs: &mut [IoSliceMut<'_>],
+        fds: &mut Vec<RawFd>,
+    ) -> crate::Result<usize> {
+        let s = match self {
+            Stream::Unix(s) => s,
+            _ => return Err(unsupported("SCM_RIGHTS", self.variant())),
+        };
+        let mut cmsg = cmsg_buffer(MAX_FDS);
+        s.async_io(Interest::READABLE, || {
+            // SAFETY: all pointers point to live buffers of the given length
+            unsafe {
+                let mut msg: libc::msghdr = mem::zeroed();
+                msg.msg_iov = bufs.as_mut_ptr() as *mut libc::iovec;
+                msg.msg_iovlen = bufs.len() as _;
+                msg.msg_control = cmsg.as_mut_ptr().cast();
+                msg.msg_controllen = mem::size_of_val(cmsg.as_slice()) as _;
+                #[cfg(any(target_os = "linux", target_os = "android"))]
+                let flags = libc::MSG_CMSG_CLOEXEC;
+                #[cfg(not(any(target_os = "linux", target_os = "android")))]
+                let flags = 0;
+                let n = match libc::recvmsg(s.as_raw_fd(), &mut msg, flags) {
+                    -1 => return Err(io::Error::last_os_error()),
+                    n => n as usize,
+                };
+                let mut hdr = libc::CMSG_FIRSTHDR(&msg);
+                while !hdr.is_null() {
+                    if (*hdr).cmsg_level == libc::SOL_SOCKET && (*hdr).cmsg_type == libc::SCM_RIGHTS
+                    {
+                        let data = libc::CMSG_DATA(hdr);
+                        let len = (*hdr).cmsg_len as usize - (data as usize - hdr as usize);
+                        for i in 0..len / mem::size_of::<RawFd>() {
+                            fds.push(ptr::read_unaligned(data.cast::<RawFd>().add(i)));
+                        }
+                    }
+                    hdr = libc::CMSG_NXTHDR(&msg, hdr);
+                }
+                if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+                    return Err(io::Error::other("too many file descriptors received"));
+                }
+                Ok(n)
+            }
+        })
+        .await
+        .map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::os::unix::io::FromRawFd;
+    use tokio::net::UnixStream;
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn pass_pipe() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (a, b) = UnixStream::pair().unwrap();
+            let (a, b) = (Stream::from(a), Stream::from(b));
+
+            let mut pipe = [0; 2];
+            assert_eq!(unsafe { libc::pipe(pipe.as_mut_ptr()) }, 0);
+            let mut tx = unsafe { std::fs::File::from_raw_fd(pipe[1]) };
+            let rx = unsafe { std::fs::File::from_raw_fd(pipe[0]) };
+
+            let n = a
+                .send_fds(&[IoSlice::new(b"fd")], &[rx.as_raw_fd()])
+                .await
+                .unwrap();
+            assert_eq!(n, 2);
+            drop(rx);
+
+            let mut buf = [0u8; 8];
+            let mut fds = Vec::new();
+            let n = b
+                .recv_fds(&mut [IoSliceMut::new(&mut buf)], &mut fds)
+                .await
+                .unwrap();
+            assert_eq!(&buf[..n], b"fd");
+            assert_eq!(fds.len(), 1);
+
+            tx.write_all(b"piped").unwrap();
+            drop(tx);
+            let mut rx = unsafe { std::fs::File::from_raw_fd(fds[0]) };
+            let mut s = String::new();
+            rx.read_to_string(&mut s).unwrap();
+            assert_eq!(s, "piped");
+        });
+    }
+    #[test]
+    fn tcp_unsupported() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = crate::stream::tests::tcp_pair().await.unwrap();
+            let e = c.send_fds(&[IoSlice::new(b"fd")], &[0]).await.unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::Unsupported);
+        });
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, Error, ReadBuf};
+use tokio::sync::Notify;
+
+use crate::{Addr, Listener, Stream};
+
+/// Number of live connections, and a way to wait for it to change
+#[derive(Default)]
+struct Live {
+    count: AtomicUsize,
+    changed: Notify,
+}
+
+/// A [`Listener`] that keeps track of its connections, created by [`Listener::into_graceful`].
+///
+/// [`GracefulListener::shutdown`] stops accepting and waits for the connections
+/// that are still open, e.g. during a rolling deploy.
+/// ```no_run
+/// # use async_stream_connection::Listener;
+/// # use std::time::Duration;
+/// # async fn doc(l: Listener) -> std::io::Result<()> {
+/// let l = l.into_graceful();
+/// let (s, _peer) = l.accept().await?;
+/// tokio::spawn(async move {
+///     // handle s
+/// #   drop(s);
+/// });
+/// if !l.shutdown(Duration::from_secs(30)).await {
+///     eprintln!("some connections are still open");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct GracefulListener {
+    listener: Listener,
+    live: Arc<Live>,
+}
+
+impl Listener {
+    /// Wraps this listener to track the connections it accepts.
+    pub fn into_graceful(self) -> GracefulListener {
+        GracefulListener {
+            listener: self,
+            live: Arc::default(),
+        }
+    }
+}
+
+impl GracefulListener {
+    /// Accepts a new incoming connection, counting it until the [`TrackedStream`] is dropped.
+    pub async fn accept(&self) -> io::Result<(TrackedStream, Addr)> {
+        let (s, a) = self.listener.accept().await?;
+        self.live.count.fetch_add(1, Ordering::SeqCst);
+        Ok((
+            TrackedStream {
+                inner: s,
+                live: self.live.clone(),
+            },
+            a,
+        ))
+    }
+    /// Gets a reference to the underlying listener.
+    pub fn get_ref(&self) -> &Listener {
+        &self.listener
+    }
+    /// Number of accepted connections that are still open.
+    pub fn connections(&self) -> usize {
+        self.live.count.load(Ordering::SeqCst)
+    }
+    /// Closes the listener and waits up to `timeout` for all connections to be dropped.
+    ///
+    /// The listener is closed right away, so no new connections are accepted while waiting.
+    /// Resolves to `true` if all connections were closed, and to `false` if the timeout elapsed first.
+    /// The remaining connections are not touched. It is up to their tasks to give up.
+    pub fn shutdown(self, timeout: Duration) -> impl Future<Output = bool> {
+        let live = self.live;
+        drop(self.listener);
+        async move {
+            let closed = async {
+                loop {
+                    // created before checking, so that no decrement is missed
+                    let changed = live.changed.notified();
+                    if live.count.load(Ordering::SeqCst) == 0 {
+                        return;
+                    }
+                    changed.await;
+                }
+            };
+            tokio::time::timeout(timeout, closed).await.is_ok()
+        }
+    }
+}
+
+/// A [`Stream`] accepted by a [`GracefulListener`].
+///
+/// It counts as open until it is dropped.
+pub struct TrackedStream {
+    inner: Stream,
+    live: Arc<Live>,
+}
+
+impl TrackedStream {
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &Stream {
+        &self.inner
+    }
+    /// Gets a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut Stream {
+        &mut self.inner
+    }
+}
+
+impl Drop for TrackedStream {
+    fn drop(&mut self) {
+        self.live.count.fetch_sub(1, Ordering::SeqCst);
+        self.live.changed.notify_waiters();
+    }
+}
+
+impl fmt::Debug for TrackedStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TrackedStream").field(&self.inner).finish()
+    }
+}
+
+impl AsyncRead for TrackedStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+impl AsyncWrite for TrackedStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<Result<usize, Error>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::runtime::Builder;
+    use tokio::time::Instant;
+
+    #[test]
+    fn shutdown_timeout() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            let a = l.local_addr().unwrap();
+            let l = l.into_graceful();
+            let _c = Stream::connect(&a).await.unwrap();
+            let (slow, _) = l.accept().await.unwrap();
+            assert_eq!(l.connections(), 1);
+
+            let start = Instant::now();
+            assert!(!l.shutdown(Duration::from_millis(50)).await);
+            assert!(start.elapsed() >= Duration::from_millis(50));
+            drop(slow);
+        });
+    }
+    #[test]
+    fn shutdown_closed() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            let a = l.local_addr().unwrap();
+            let l = l.into_graceful();
+            let _c = Stream::connect(&a).await.unwrap();
+            let (s, _) = l.accept().await.unwrap();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                drop(s);
+            });
+            assert!(l.shutdown(Duration::from_secs(10)).await);
+        });
+    }
+}
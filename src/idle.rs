@@ -0,0 +1,133 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, Error, ReadBuf};
+use tokio::time::{Instant, Sleep};
+
+/// Fails reads and writes on the wrapped stream after a period of inactivity.
+///
+/// If neither a read nor a write made progress for the idle timeout,
+/// the pending operation returns an error of kind [`io::ErrorKind::TimedOut`].
+/// ```
+/// # use async_stream_connection::{Stream, TimeoutStream};
+/// # use std::time::Duration;
+/// # async fn doc(s: Stream) {
+/// let s = TimeoutStream::new(s, Duration::from_secs(60));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TimeoutStream<S> {
+    inner: S,
+    timeout: Duration,
+    sleep: Pin<Box<Sleep>>,
+}
+
+impl<S> TimeoutStream<S> {
+    /// Wraps `inner`, starting the idle timer right away.
+    pub fn new(inner: S, timeout: Duration) -> TimeoutStream<S> {
+        TimeoutStream {
+            inner,
+            timeout,
+            sleep: Box::pin(tokio::time::sleep(timeout)),
+        }
+    }
+    /// The idle timeout
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+    /// Gets a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+    /// Re-arms the timer after progress, checks it otherwise
+    fn check<T>(&mut self, cx: &mut Context, r: Poll<io::Result<T>>) -> Poll<io::Result<T>> {
+        match r {
+            Poll::Pending => match self.sleep.as_mut().poll(cx) {
+                Poll::Ready(()) => Poll::Ready(Err(io::ErrorKind::TimedOut.into())),
+                Poll::Pending => Poll::Pending,
+            },
+            r => {
+                let deadline = Instant::now() + self.timeout;
+                self.sleep.as_mut().reset(deadline);
+                r
+            }
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for TimeoutStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        let r = Pin::new(&mut self.inner).poll_read(cx, buf);
+        self.check(cx, r)
+    }
+}
+impl<S: AsyncWrite + Unpin> AsyncWrite for TimeoutStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        let r = Pin::new(&mut self.inner).poll_write(cx, buf);
+        self.check(cx, r)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<Result<usize, Error>> {
+        let r = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+        self.check(cx, r)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::tests::tcp_pair;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn silent_peer() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, mut s) = tcp_pair().await.unwrap();
+            let mut c = TimeoutStream::new(c, Duration::from_millis(100));
+            s.write_all(b"1").await.unwrap();
+            let mut buf = [0u8; 1];
+            c.read_exact(&mut buf).await.unwrap();
+
+            let start = std::time::Instant::now();
+            let e = c.read(&mut buf).await.unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+            assert!(start.elapsed() >= Duration::from_millis(100));
+        });
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::str::FromStr;
+
+use crate::Listener;
+
+/// A listening socket inherited from the parent process, by its file descriptor.
+///
+/// It is written as `fd://N`, e.g. in a config file next to [`Addr`](crate::Addr)s:
+/// ```
+/// # use async_stream_connection::InheritedFd;
+/// # fn main() -> Result<(),std::io::Error> {
+/// let fd: InheritedFd = "fd://3".parse()?;
+/// assert_eq!(fd.fd(), 3);
+/// # Ok(())
+/// # }
+/// ```
+/// Parsing does not touch the fd. [`Listener::bind_inherited`] takes it over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InheritedFd(RawFd);
+
+impl InheritedFd {
+    /// Refers to the inherited socket `fd`.
+    pub fn new(fd: RawFd) -> InheritedFd {
+        InheritedFd(fd)
+    }
+    /// The file descriptor
+    pub fn fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+impl FromStr for InheritedFd {
+    type Err = io::Error;
+
+    fn from_str(v: &str) -> Result<Self, Self::Err> {
+        v.strip_prefix("fd://")
+            .and_then(|n| n.parse().ok())
+            .filter(|&n: &RawFd| n >= 0)
+            .map(InheritedFd)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "expected fd://N"))
+    }
+}
+
+impl fmt::Display for InheritedFd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "fd://{}", self.0)
+    }
+}
+
+/// Serializes to the same string [`fmt::Display`] produces, so that it can be deserialized again
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl serde::ser::Serialize for InheritedFd {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> serde::de::Deserialize<'de> for InheritedFd {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = InheritedFd;
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("an inherited socket, like fd://3")
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                InheritedFd::from_str(v).map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+impl Listener {
+    /// Takes over the inherited, already listening socket `fd`,
+    /// like [`Listener::from_raw_fd_checked`].
+    ///
+    /// # Safety
+    ///
+    /// The fd must be a socket that is not owned by anything else.
+    /// The Listener owns it from then on, so the same `fd` must not be bound twice.
+    pub unsafe fn bind_inherited(fd: InheritedFd) -> io::Result<Listener> {
+        Listener::from_raw_fd_checked(fd.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn parse() {
+        let fd: InheritedFd = "fd://3".parse().unwrap();
+        assert_eq!(fd, InheritedFd::new(3));
+        assert_eq!(fd.to_string(), "fd://3");
+        for v in ["fd://", "fd://-1", "fd://x", "/tmp/fd://3", "3"] {
+            let e = v.parse::<InheritedFd>().unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        }
+        // no longer an address
+        assert!("fd://3".parse::<crate::Addr>().is_err());
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn bind_inherited() {
+        use crate::{Addr, Stream};
+        use std::os::unix::io::IntoRawFd;
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let _ = std::fs::remove_file("/tmp/asc_fd.sock");
+            let l = std::os::unix::net::UnixListener::bind("/tmp/asc_fd.sock").unwrap();
+            let fd: InheritedFd = format!("fd://{}", l.into_raw_fd()).parse().unwrap();
+            let l = unsafe { Listener::bind_inherited(fd) }.unwrap();
+            assert!(matches!(l, Listener::Unix(_, None, false)));
+            let path: Addr = "/tmp/asc_fd.sock".parse().unwrap();
+            let (c, s) = tokio::join!(Stream::connect(&path), l.accept());
+            c.unwrap();
+            s.unwrap();
+            drop(l);
+            // an inherited socket file is left alone
+            assert!(std::path::Path::new("/tmp/asc_fd.sock").exists());
+            std::fs::remove_file("/tmp/asc_fd.sock").unwrap();
+        });
+    }
+    #[test]
+    fn bind_inherited_tcp() {
+        use std::os::unix::io::IntoRawFd;
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let a = l.local_addr().unwrap();
+            let fd = InheritedFd::new(l.into_raw_fd());
+            let l = unsafe { Listener::bind_inherited(fd) }.unwrap();
+            assert_eq!(l.local_addr().unwrap(), crate::Addr::Inet(a));
+        });
+    }
+}
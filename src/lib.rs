@@ -45,10 +45,87 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 mod addr;
+mod error;
 mod stream;
 mod listener;
+mod split;
+mod builder;
+mod proxy;
+mod peekable;
+mod counting;
+mod idle;
+mod deadline;
+mod write_timeout;
+mod reconnect;
+mod shared;
+mod cached;
+mod message;
+mod serve;
+mod graceful;
+mod trace;
+mod pool;
+mod resolver;
+mod tcp_info;
+mod buffered;
+#[cfg(unix)]
+mod inherited;
+#[cfg(all(unix, feature = "unix"))]
+mod fd_passing;
+#[cfg(windows)]
+mod pipe;
+#[cfg(feature = "datagram")]
+mod datagram;
+#[cfg(feature = "rustls")]
+mod tls;
+#[cfg(feature = "codec")]
+mod codec;
+#[cfg(feature = "socks")]
+mod socks;
 
-pub use addr::Addr;
-pub use stream::Stream;
-pub use listener::Listener;
+pub use addr::{Addr, AddrFamily, IpPreference};
+pub use error::{Error, Result};
+pub use stream::{StdStream, Stream};
+pub use listener::{Listener, StdListener};
+pub use builder::ListenerBuilder;
+pub use peekable::PeekableStream;
+pub use counting::CountingStream;
+pub use idle::TimeoutStream;
+pub use deadline::DeadlineStream;
+pub use write_timeout::WriteTimeoutStream;
+pub use reconnect::{Backoff, ReconnectStream};
+pub use shared::SharedStream;
+pub use cached::CachedAddrStream;
+pub use pool::{Pool, PooledStream};
+pub use graceful::{GracefulListener, TrackedStream};
+pub use resolver::{Resolver, SystemResolver};
+pub use tcp_info::TcpInfo;
+#[cfg(unix)]
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+pub use inherited::InheritedFd;
+#[cfg(windows)]
+#[cfg_attr(docsrs, doc(cfg(windows)))]
+pub use pipe::PipeListener;
+#[cfg(feature = "datagram")]
+#[cfg_attr(docsrs, doc(cfg(feature = "datagram")))]
+pub use datagram::Datagram;
+#[cfg(feature = "rustls")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+pub use tls::TlsStream;
+#[cfg(feature = "rustls")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+pub use tokio_rustls;
+#[cfg(feature = "codec")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codec")))]
+pub use tokio_util;
+#[cfg(feature = "stream")]
+pub use listener::Incoming;
+pub use socket2::TcpKeepalive;
+pub use tokio::io::BufStream;
+pub use split::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, WriteHalf};
+
+/// Error for operations that do not exist on the given socket type.
+#[cfg(any(unix, windows))]
+pub(crate) fn unsupported(op: &'static str, variant: &'static str) -> Error {
+    Error::Unsupported { op, variant }
+}
 
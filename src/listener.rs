@@ -1,12 +1,28 @@
 use tokio::net::TcpListener;
-#[cfg(unix)]
-use tokio::net::UnixListener;
+#[cfg(all(unix, feature = "unix"))]
+use tokio::net::{unix::UCred, UnixListener};
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+use tokio_vsock::{VsockAddr, VsockListener};
 
+use socket2::SockRef;
+use std::future::Future;
 use std::io;
+use std::net::IpAddr;
+use std::ops::RangeInclusive;
+use std::task::{Context, Poll};
+use std::time::Duration;
 #[cfg(unix)]
 use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(all(unix, feature = "unix"))]
+use std::path::{Path, PathBuf};
 
+#[cfg(all(unix, feature = "unix"))]
+use crate::addr::socket_file;
 use crate::{Addr, Stream};
+#[cfg(windows)]
+use crate::PipeListener;
+#[cfg(any(all(unix, feature = "unix"), windows, all(feature = "vsock", target_os = "linux")))]
+use crate::unsupported;
 
 /// A socket server, listening for connections.
 ///
@@ -14,19 +30,210 @@ use crate::{Addr, Stream};
 pub enum Listener {
     /// A TCP socket server, listening for connections.
     Inet(TcpListener),
-    #[cfg(unix)]
+    #[cfg(all(unix, feature = "unix"))]
     /// A Unix socket which can accept connections from other Unix sockets.
-    Unix(UnixListener),
+    ///
+    /// The path is the socket file of the listener, if it is known to have one.
+    /// The flag says whether that file is removed on drop, see [`Listener::set_unlink_on_drop`].
+    Unix(UnixListener, Option<PathBuf>, bool),
+    #[cfg(windows)]
+    /// A Windows named pipe server.
+    Pipe(PipeListener),
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "vsock", target_os = "linux"))))]
+    /// A vsock socket, accepting connections from VMs or their host.
+    Vsock(VsockListener),
 }
 impl Listener {
     /// Creates a new Listener, which will be bound to the specified address.
     ///
     /// The returned listener is ready for accepting connections.
+    /// To take over an inherited socket, see [`Listener::bind_inherited`].
     pub async fn bind(s: &Addr) -> io::Result<Listener> {
         match s {
             Addr::Inet(s) => TcpListener::bind(s).await.map(Listener::Inet),
-            #[cfg(unix)]
-            Addr::Unix(s) => UnixListener::bind(s).map(Listener::Unix),
+            #[cfg(all(unix, feature = "unix"))]
+            Addr::Unix(s) => UnixListener::bind(s).map(|l| Listener::Unix(l, socket_file(s), true)),
+            #[cfg(windows)]
+            Addr::Pipe(s) => PipeListener::bind(s).map(Listener::Pipe),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Addr::Vsock { cid, port } => {
+                VsockListener::bind(VsockAddr::new(*cid, *port)).map(Listener::Vsock)
+            }
+        }
+    }
+    /// Like [`Listener::bind`], but takes anything that converts into an [`Addr`],
+    /// like a [`SocketAddr`](std::net::SocketAddr) or a [`Path`](std::path::Path).
+    pub async fn bind_to<A: Into<Addr>>(a: A) -> io::Result<Listener> {
+        Listener::bind(&a.into()).await
+    }
+    /// Binds to the first of `addrs` that works, like the results of [`Addr::resolve_all`].
+    ///
+    /// If all of them fail, the returned error lists each address along with its error
+    /// and is of the kind of the last one.
+    pub async fn bind_any(addrs: &[Addr]) -> io::Result<Listener> {
+        let mut errors = Vec::new();
+        let mut kind = io::ErrorKind::InvalidInput;
+        for a in addrs {
+            match Listener::bind(a).await {
+                Ok(l) => return Ok(l),
+                Err(e) => {
+                    kind = e.kind();
+                    errors.push(format!("{}: {}", a, e));
+                }
+            }
+        }
+        if errors.is_empty() {
+            return Err(io::Error::new(kind, "no address to bind to"));
+        }
+        Err(io::Error::new(kind, errors.join(", ")))
+    }
+    /// Binds a TCP listener to the first port of `ports` on `ip` that is not in use.
+    ///
+    /// The chosen port can be read via [`Listener::local_addr`].
+    /// Errors other than [`io::ErrorKind::AddrInUse`] are returned right away.
+    /// If every port is in use, the last error is returned.
+    pub async fn bind_in_range(ip: IpAddr, ports: RangeInclusive<u16>) -> io::Result<Listener> {
+        let mut last = io::Error::new(io::ErrorKind::InvalidInput, "empty port range");
+        for port in ports {
+            match TcpListener::bind((ip, port)).await {
+                Ok(l) => return Ok(Listener::Inet(l)),
+                Err(e) if e.kind() == io::ErrorKind::AddrInUse => last = e,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last)
+    }
+    /// Like [`Listener::bind`], but retries up to `attempts` times in total
+    /// while the address is in use or not (yet) available.
+    ///
+    /// The delay between attempts starts at `base_delay` and doubles each time.
+    /// This helps when a restarted server races against the sockets of its predecessor.
+    /// Other errors are returned right away.
+    pub async fn bind_retry(
+        addr: &Addr,
+        attempts: u32,
+        base_delay: Duration,
+    ) -> io::Result<Listener> {
+        let mut delay = base_delay;
+        let mut attempt = 1;
+        loop {
+            match Listener::bind(addr).await {
+                Err(e)
+                    if attempt < attempts
+                        && matches!(
+                            e.kind(),
+                            io::ErrorKind::AddrInUse | io::ErrorKind::AddrNotAvailable
+                        ) =>
+                {
+                    tokio::time::sleep(delay).await;
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                r => return r,
+            }
+        }
+    }
+    /// Creates a new Listener from a [`std::net::TcpListener`].
+    ///
+    /// The listener is put into nonblocking mode.
+    pub fn from_std_tcp(l: std::net::TcpListener) -> io::Result<Listener> {
+        l.set_nonblocking(true)?;
+        TcpListener::from_std(l).map(Listener::Inet)
+    }
+    /// Creates a new Listener from a [`std::os::unix::net::UnixListener`].
+    ///
+    /// The listener is put into nonblocking mode.
+    /// Its socket file is not removed on drop, unless [`Listener::set_unlink_on_drop`] says so.
+    #[cfg(all(unix, feature = "unix"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+    pub fn from_std_unix(l: std::os::unix::net::UnixListener) -> io::Result<Listener> {
+        l.set_nonblocking(true)?;
+        UnixListener::from_std(l).map(|l| Listener::Unix(l, None, false))
+    }
+    /// Creates a new Listener from an inherited socket, e.g. passed by systemd (`LISTEN_FDS`).
+    ///
+    /// The socket family is inspected to decide whether a TCP or Unix listener is created.
+    /// The socket is put into nonblocking mode.
+    /// A Unix socket file is not removed on drop, unless [`Listener::set_unlink_on_drop`] says so.
+    /// If the fd is not a listening stream socket of a supported family, an error of kind
+    /// [`io::ErrorKind::InvalidInput`] is returned and the fd is left open.
+    /// Whether it listens can't be checked on every OS (e.g. macOS).
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open socket that is not owned by anything else.
+    /// On success, the returned Listener takes ownership of it.
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub unsafe fn from_raw_fd_checked(fd: RawFd) -> io::Result<Listener> {
+        use socket2::{Socket, Type};
+        use std::os::unix::io::FromRawFd;
+
+        // don't close the fd if we reject it
+        let socket = std::mem::ManuallyDrop::new(Socket::from_raw_fd(fd));
+        if socket.r#type()? != Type::STREAM {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not a stream socket",
+            ));
+        }
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+        if !socket.is_listener()? {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not a listening socket",
+            ));
+        }
+        let addr = socket.local_addr()?;
+        if addr.is_ipv4() || addr.is_ipv6() {
+            socket.set_nonblocking(true)?;
+            return TcpListener::from_std(std::mem::ManuallyDrop::into_inner(socket).into())
+                .map(Listener::Inet);
+        }
+        #[cfg(feature = "unix")]
+        if addr.is_unix() {
+            socket.set_nonblocking(true)?;
+            return UnixListener::from_std(std::mem::ManuallyDrop::into_inner(socket).into())
+                .map(|l| Listener::Unix(l, None, false));
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "unsupported socket family",
+        ))
+    }
+    /// Turns this Listener into its std counterpart.
+    ///
+    /// The returned listener will be in nonblocking mode.
+    /// A Unix socket file is not removed, as it is now owned by the returned listener.
+    /// Named pipes and vsock sockets have no std counterpart and return an error of kind
+    /// [`io::ErrorKind::Unsupported`].
+    pub fn into_std(self) -> io::Result<StdListener> {
+        // `Listener` implements `Drop` on unix, so the inner listener can't simply be moved out
+        let this = std::mem::ManuallyDrop::new(self);
+        match &*this {
+            // SAFETY: `this` is never dropped, so the inner listener is only owned once
+            Listener::Inet(l) => unsafe { std::ptr::read(l) }
+                .into_std()
+                .map(StdListener::Inet),
+            #[cfg(all(unix, feature = "unix"))]
+            // SAFETY: see above
+            Listener::Unix(l, path, _) => {
+                drop(unsafe { std::ptr::read(path) });
+                unsafe { std::ptr::read(l) }
+                    .into_std()
+                    .map(StdListener::Unix)
+            }
+            #[cfg(windows)]
+            Listener::Pipe(_) => {
+                drop(std::mem::ManuallyDrop::into_inner(this));
+                Err(unsupported("into_std", "Listener::Pipe").into())
+            }
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(_) => {
+                drop(std::mem::ManuallyDrop::into_inner(this));
+                Err(unsupported("into_std", "Listener::Vsock").into())
+            }
         }
     }
     /// Accepts a new incoming connection from this listener.
@@ -34,38 +241,843 @@ impl Listener {
     /// This function will yield once a new connection is established.
     /// When established, the corresponding [`Stream`] and the remote peer’s address will be returned.
     pub async fn accept(&self) -> io::Result<(Stream, Addr)> {
+        let r = self.accept_inner().await;
+        crate::trace::accepted(self, &r);
+        r
+    }
+    /// Like [`Listener::accept`], but also returns the `tracing` span of the connection.
+    ///
+    /// The "accepted" event is emitted inside of it.
+    /// Instrument the handler with it, so that its events (and those of this crate)
+    /// are attributed to the connection:
+    /// ```no_run
+    /// # use async_stream_connection::Listener;
+    /// use tracing::Instrument;
+    /// # async fn doc(l: Listener) -> std::io::Result<()> {
+    /// let (s, _peer, span) = l.accept_with_span().await?;
+    /// tokio::spawn(async move {
+    ///     // handle s
+    /// #   drop(s);
+    /// }.instrument(span));
+    /// # Ok(())
+    /// # }
+    /// ```
+    /// A "closed" event is only emitted if the stream is shut down successfully,
+    /// e.g. by [`AsyncWriteExt::shutdown`](tokio::io::AsyncWriteExt::shutdown).
+    /// Dropping a stream closes it silently.
+    #[cfg(feature = "tracing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+    pub async fn accept_with_span(&self) -> io::Result<(Stream, Addr, tracing::Span)> {
+        let r = self.accept_inner().await;
+        let span = crate::trace::accepted(self, &r);
+        r.map(|(s, a)| (s, a, span))
+    }
+    async fn accept_inner(&self) -> io::Result<(Stream, Addr)> {
         match self {
             Listener::Inet(s) => s
                 .accept()
                 .await
                 .map(|(s, a)| (Stream::Inet(s), Addr::Inet(a))),
-            #[cfg(unix)]
-            Listener::Unix(s) => s
+            #[cfg(all(unix, feature = "unix"))]
+            Listener::Unix(s, ..) => s
                 .accept()
                 .await
                 .map(|(s, a)| (Stream::Unix(s), Addr::from(a))),
+            #[cfg(windows)]
+            Listener::Pipe(p) => p
+                .accept()
+                .await
+                .map(|s| (Stream::PipeServer(s), Addr::Pipe(p.name().to_string()))),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(s) => s
+                .accept()
+                .await
+                .map(|(s, a)| (Stream::Vsock(s), Addr::from(a))),
+        }
+    }
+    /// Like [`Listener::accept`], but also returns the credentials of the connecting process
+    /// for Unix sockets, e.g. to authenticate it by uid.
+    ///
+    /// They are read right after accepting, see [`Stream::peer_cred`].
+    /// For other listeners, they are `None`.
+    #[cfg(all(unix, feature = "unix"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+    pub async fn accept_with_cred(&self) -> io::Result<(Stream, Addr, Option<UCred>)> {
+        let (s, a) = self.accept().await?;
+        let cred = match &s {
+            Stream::Unix(u) => Some(u.peer_cred()?),
+            _ => None,
+        };
+        Ok((s, a, cred))
+    }
+    /// Accepts a new incoming connection, unless `shutdown` completes first.
+    ///
+    /// Returns `Ok(None)` once `shutdown` completes, so that an accept loop can end cleanly:
+    /// ```no_run
+    /// # use async_stream_connection::Listener;
+    /// # async fn doc(l: Listener, stop: tokio::sync::oneshot::Receiver<()>) -> std::io::Result<()> {
+    /// let mut stop = stop;
+    /// while let Some((s, peer)) = l.accept_or_shutdown(&mut stop).await? {
+    ///     // handle s
+    /// #   let _ = (s, peer);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    /// `shutdown` is checked first. A connection that arrives at the same time is not accepted,
+    /// it stays in the backlog of the listener.
+    pub async fn accept_or_shutdown(
+        &self,
+        shutdown: impl Future,
+    ) -> io::Result<Option<(Stream, Addr)>> {
+        let mut shutdown = std::pin::pin!(shutdown);
+        let mut accept = std::pin::pin!(self.accept());
+        std::future::poll_fn(|cx| {
+            if shutdown.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(Ok(None));
+            }
+            accept.as_mut().poll(cx).map_ok(Some)
+        })
+        .await
+    }
+    /// Accepts a new incoming connection, but only from peers that `allow` returns `true` for.
+    ///
+    /// Connections from other peers are closed right away, and the next one is awaited.
+    /// This is meant as a lightweight allowlist, for example on the peer's IP.
+    pub async fn accept_filtered(
+        &self,
+        allow: impl Fn(&Addr) -> bool,
+    ) -> io::Result<(Stream, Addr)> {
+        loop {
+            let (s, a) = self.accept().await?;
+            if allow(&a) {
+                return Ok((s, a));
+            }
+            drop(s);
+        }
+    }
+    /// Accepts a new incoming connection, without its peer address.
+    ///
+    /// Like [`Listener::accept`], but does not convert the address into an [`Addr`].
+    /// For Unix sockets this saves building a [`PathBuf`](std::path::PathBuf) per connection.
+    pub async fn accept_stream(&self) -> io::Result<Stream> {
+        match self {
+            Listener::Inet(s) => s.accept().await.map(|(s, _)| Stream::Inet(s)),
+            #[cfg(all(unix, feature = "unix"))]
+            Listener::Unix(s, ..) => s.accept().await.map(|(s, _)| Stream::Unix(s)),
+            #[cfg(windows)]
+            Listener::Pipe(p) => p.accept().await.map(Stream::PipeServer),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(s) => s.accept().await.map(|(s, _)| Stream::Vsock(s)),
+        }
+    }
+    /// Polls to accept a new incoming connection to this listener.
+    ///
+    /// If there is no connection to accept, `Poll::Pending` is returned and the current task will be notified by a waker.
+    /// Note that on multiple calls to `poll_accept`, only the `Waker` from the `Context` passed to the most recent call is scheduled to receive a wakeup.
+    ///
+    /// Named pipes can't be polled and return an error of kind [`io::ErrorKind::Unsupported`].
+    pub fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<io::Result<(Stream, Addr)>> {
+        let r = match self {
+            Listener::Inet(s) => s
+                .poll_accept(cx)
+                .map_ok(|(s, a)| (Stream::Inet(s), Addr::Inet(a))),
+            #[cfg(all(unix, feature = "unix"))]
+            Listener::Unix(s, ..) => s
+                .poll_accept(cx)
+                .map_ok(|(s, a)| (Stream::Unix(s), Addr::from(a))),
+            #[cfg(windows)]
+            Listener::Pipe(_) => {
+                let _ = cx;
+                Poll::Ready(Err(unsupported("poll_accept", "Listener::Pipe").into()))
+            }
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(s) => s
+                .poll_accept(cx)
+                .map_ok(|(s, a)| (Stream::Vsock(s), Addr::from(a))),
+        };
+        if let Poll::Ready(r) = &r {
+            crate::trace::accepted(self, r);
+        }
+        r
+    }
+    /// Name of the variant, for error messages
+    pub(crate) fn variant(&self) -> &'static str {
+        match self {
+            Listener::Inet(_) => "Listener::Inet",
+            #[cfg(all(unix, feature = "unix"))]
+            Listener::Unix(..) => "Listener::Unix",
+            #[cfg(windows)]
+            Listener::Pipe(_) => "Listener::Pipe",
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(_) => "Listener::Vsock",
+        }
+    }
+    /// Sets whether the socket file of a Unix listener is removed when the listener is dropped.
+    ///
+    /// This is on for listeners created by [`Listener::bind`] and off for inherited ones,
+    /// as the file might still be used by someone else.
+    /// It can be changed at any time, e.g. during a graceful shutdown,
+    /// depending on whether a replacement process will take over the file.
+    /// Other listeners have no socket file and ignore this.
+    #[cfg(all(unix, feature = "unix"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+    pub fn set_unlink_on_drop(&mut self, unlink: bool) {
+        if let Listener::Unix(l, path, unlink_on_drop) = self {
+            if unlink && path.is_none() {
+                *path = l
+                    .local_addr()
+                    .ok()
+                    .and_then(|a| a.as_pathname().map(Path::to_path_buf));
+            }
+            *unlink_on_drop = unlink;
+        }
+    }
+    /// Keeps the socket file of a Unix listener when it is dropped,
+    /// e.g. so that clients can still connect while a supervisor restarts the process.
+    ///
+    /// Returns the listener along with the path of the file it would have removed,
+    /// which is now up to the caller to clean up.
+    /// The path is `None` if nothing would have been removed, like for other listeners.
+    /// See also [`Listener::set_unlink_on_drop`].
+    #[cfg(all(unix, feature = "unix"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+    pub fn leak_socket(mut self) -> (Listener, Option<PathBuf>) {
+        if let Listener::Unix(_, path, unlink) = &mut self {
+            let path = path.clone().filter(|_| *unlink);
+            *unlink = false;
+            return (self, path);
+        }
+        (self, None)
+    }
+    /// Returns the local address that this listener is bound to.
+    ///
+    /// This can be used to find the port the OS picked when binding to port 0.
+    /// For a Unix listener with a known socket file, that file is returned.
+    /// It differs from the address the OS reports if the file was renamed,
+    /// see [`ListenerBuilder::bind_unix_atomic`](crate::ListenerBuilder::bind_unix_atomic).
+    pub fn local_addr(&self) -> io::Result<Addr> {
+        match self {
+            Listener::Inet(s) => s.local_addr().map(Addr::Inet),
+            #[cfg(all(unix, feature = "unix"))]
+            Listener::Unix(_, Some(p), _) => Ok(Addr::Unix(p.clone())),
+            #[cfg(all(unix, feature = "unix"))]
+            Listener::Unix(s, None, _) => s.local_addr().map(Addr::from),
+            #[cfg(windows)]
+            Listener::Pipe(p) => Ok(Addr::Pipe(p.name().to_string())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(s) => s.local_addr().map(Addr::from),
+        }
+    }
+    /// Checks if this listener is still listening for connections.
+    ///
+    /// Where available, `SO_ACCEPTCONN` is asked, so that a socket that was shut down
+    /// (e.g. via a raw fd) is detected. Elsewhere this is best-effort and only checks
+    /// that the local address can still be read.
+    pub fn is_listening(&self) -> bool {
+        match self {
+            #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+            Listener::Inet(s) => SockRef::from(s).is_listener().unwrap_or(false),
+            #[cfg(all(
+                any(target_os = "linux", target_os = "android", target_os = "freebsd"),
+                feature = "unix"
+            ))]
+            Listener::Unix(s, ..) => SockRef::from(s).is_listener().unwrap_or(false),
+            // there might be no other listeners
+            #[allow(unreachable_patterns)]
+            _ => self.local_addr().is_ok(),
+        }
+    }
+    /// Turns this listener into a [`futures_core::Stream`] of incoming connections.
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub fn incoming(self) -> Incoming {
+        Incoming { listener: self }
+    }
+    /// Gets the value of the `IP_TTL` option for this socket.
+    ///
+    /// Unix sockets have no such option and return [`Error::Unsupported`](crate::Error::Unsupported).
+    pub fn ttl(&self) -> crate::Result<u32> {
+        match self {
+            Listener::Inet(s) => Ok(s.ttl()?),
+            #[cfg(all(unix, feature = "unix"))]
+            Listener::Unix(..) => Err(unsupported("IP_TTL", self.variant())),
+            #[cfg(windows)]
+            Listener::Pipe(_) => Err(unsupported("IP_TTL", self.variant())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(_) => Err(unsupported("IP_TTL", self.variant())),
+        }
+    }
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet sent from this socket.
+    /// Unix sockets have no such option and return [`Error::Unsupported`](crate::Error::Unsupported).
+    pub fn set_ttl(&self, ttl: u32) -> crate::Result<()> {
+        match self {
+            Listener::Inet(s) => Ok(s.set_ttl(ttl)?),
+            #[cfg(all(unix, feature = "unix"))]
+            Listener::Unix(..) => Err(unsupported("IP_TTL", self.variant())),
+            #[cfg(windows)]
+            Listener::Pipe(_) => Err(unsupported("IP_TTL", self.variant())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(_) => Err(unsupported("IP_TTL", self.variant())),
+        }
+    }
+    /// Moves this socket into or out of nonblocking mode (`O_NONBLOCK` / `FIONBIO`).
+    ///
+    /// Tokio requires nonblocking sockets. Only switch to blocking mode right before
+    /// handing the socket off to code that accepts on its own,
+    /// and switch back before using this `Listener` again.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> crate::Result<()> {
+        match self {
+            Listener::Inet(s) => Ok(SockRef::from(s).set_nonblocking(nonblocking)?),
+            #[cfg(all(unix, feature = "unix"))]
+            Listener::Unix(s, ..) => Ok(SockRef::from(s).set_nonblocking(nonblocking)?),
+            #[cfg(windows)]
+            Listener::Pipe(_) => Err(unsupported("socket options", self.variant())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(_) => Err(unsupported("socket options", self.variant())),
         }
     }
 }
+/// A blocking std listener, returned by [`Listener::into_std`].
+#[derive(Debug)]
+pub enum StdListener {
+    /// A TCP socket server
+    Inet(std::net::TcpListener),
+    #[cfg(all(unix, feature = "unix"))]
+    /// A Unix socket server
+    Unix(std::os::unix::net::UnixListener),
+}
+
+/// Stream of incoming connections, created by [`Listener::incoming`].
+///
+/// ```no_run
+/// # use async_stream_connection::Listener;
+/// use futures_util::StreamExt;
+/// # async fn doc() -> std::io::Result<()> {
+/// let mut incoming = Listener::bind(&"127.0.0.1:8080".parse()?).await?.incoming();
+/// while let Some(con) = incoming.next().await {
+///     let (socket, peer) = con?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub struct Incoming {
+    listener: Listener,
+}
+#[cfg(feature = "stream")]
+impl Incoming {
+    /// Returns the wrapped [`Listener`].
+    pub fn into_inner(self) -> Listener {
+        self.listener
+    }
+}
+#[cfg(feature = "stream")]
+impl AsRef<Listener> for Incoming {
+    fn as_ref(&self) -> &Listener {
+        &self.listener
+    }
+}
+#[cfg(feature = "stream")]
+impl futures_core::Stream for Incoming {
+    type Item = io::Result<(Stream, Addr)>;
+
+    fn poll_next(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.listener.poll_accept(cx).map(Some)
+    }
+}
 #[cfg(unix)]
 impl AsRawFd for Listener {
     fn as_raw_fd(&self) -> RawFd {
         match self {
             Listener::Inet(s) => s.as_raw_fd(),
-            #[cfg(unix)]
-            Listener::Unix(s) => s.as_raw_fd(),
+            #[cfg(all(unix, feature = "unix"))]
+            Listener::Unix(s, ..) => s.as_raw_fd(),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(s) => s.as_raw_fd(),
         }
     }
 }
-#[cfg(unix)]
+/// Removes the socket file of a Unix listener, if [`Listener::set_unlink_on_drop`] is set.
+///
+/// Unnamed and abstract sockets have no file.
+/// Errors (e.g. the file is already gone) are ignored, as there is nobody to report them to.
+#[cfg(all(unix, feature = "unix"))]
 impl Drop for Listener {
     fn drop(&mut self) {
-        if let Listener::Unix(l) = self {
-            if let Ok(a) = l.local_addr() {
-                if let Some(path) = a.as_pathname() {
-                    std::fs::remove_file(path).unwrap();
+        if let Listener::Unix(_, Some(path), true) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn poll_accept() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            let a = l.local_addr().unwrap();
+            let client = tokio::spawn(async move { Stream::connect(&a).await.unwrap() });
+            let (s, peer) = std::future::poll_fn(|cx| l.poll_accept(cx)).await.unwrap();
+            let c = client.await.unwrap();
+            assert_eq!(peer, c.local_addr().unwrap());
+            assert_eq!(s.peer_addr().unwrap(), c.local_addr().unwrap());
+        });
+    }
+    #[test]
+    fn bind_in_range() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let ip = IpAddr::from([127, 0, 0, 1]);
+            // find two free ports next to each other
+            let (held, port) = loop {
+                let held = std::net::TcpListener::bind((ip, 0)).unwrap();
+                let port = held.local_addr().unwrap().port();
+                if port < u16::MAX && std::net::TcpListener::bind((ip, port + 1)).is_ok() {
+                    break (held, port);
                 }
+            };
+            let l = Listener::bind_in_range(ip, port..=port + 1).await.unwrap();
+            assert_eq!(l.local_addr().unwrap(), Addr::Inet((ip, port + 1).into()));
+            let e = Listener::bind_in_range(ip, port..=port).await.err().unwrap();
+            assert_eq!(e.kind(), io::ErrorKind::AddrInUse);
+            drop(held);
+        });
+    }
+    #[test]
+    fn bind_retry() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let held = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let a = Addr::Inet(held.local_addr().unwrap());
+            let e = Listener::bind_retry(&a, 1, Duration::from_millis(10))
+                .await
+                .err()
+                .unwrap();
+            assert_eq!(e.kind(), io::ErrorKind::AddrInUse);
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                drop(held);
+            });
+            let l = Listener::bind_retry(&a, 6, Duration::from_millis(10))
+                .await
+                .unwrap();
+            assert_eq!(l.local_addr().unwrap(), a);
+        });
+    }
+    #[test]
+    fn accept_or_shutdown() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            let a = l.local_addr().unwrap();
+            let (c, s) = tokio::join!(
+                Stream::connect(&a),
+                l.accept_or_shutdown(std::future::pending::<()>())
+            );
+            c.unwrap();
+            assert!(s.unwrap().is_some());
+
+            let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                drop(tx);
+            });
+            assert!(l.accept_or_shutdown(rx).await.unwrap().is_none());
+        });
+    }
+    #[test]
+    fn is_listening() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            assert!(l.is_listening());
+            assert!(l.local_addr().unwrap().is_inet());
+            #[cfg(all(unix, feature = "unix"))]
+            {
+                let path = "/tmp/asc_is_listening.sock";
+                let _ = std::fs::remove_file(path);
+                let l = Listener::bind(&Addr::Unix(path.into())).await.unwrap();
+                assert!(l.is_listening());
+                assert!(l.local_addr().unwrap().is_unix());
             }
-        }
+        });
+    }
+    #[test]
+    fn accept_filtered() {
+        use tokio::io::AsyncReadExt;
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            let a = l.local_addr().unwrap();
+            let mut rejected = Stream::connect(&a).await.unwrap();
+            let accepted = Stream::connect(&a).await.unwrap();
+            let denied = rejected.local_addr().unwrap();
+            let (_s, peer) = l.accept_filtered(|p| p != &denied).await.unwrap();
+            assert_eq!(peer, accepted.local_addr().unwrap());
+            let mut buf = [0u8; 1];
+            assert!(!matches!(rejected.read(&mut buf).await, Ok(1)));
+        });
+    }
+    #[test]
+    fn bind_to() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let a: std::net::SocketAddr = "127.0.0.1:0".parse().unwrap();
+            let l = Listener::bind_to(a).await.unwrap();
+            assert!(l.local_addr().unwrap().is_inet());
+        });
+    }
+    #[test]
+    fn set_nonblocking() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            l.set_nonblocking(false).unwrap();
+            l.set_nonblocking(true).unwrap();
+            let a = l.local_addr().unwrap();
+            let (c, s) = tokio::join!(Stream::connect(&a), l.accept());
+            c.unwrap();
+            s.unwrap();
+        });
+    }
+    // only these can tell whether a socket listens
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    #[test]
+    fn from_raw_fd_not_listening() {
+        use std::os::unix::io::{FromRawFd, IntoRawFd, OwnedFd};
+        let l = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let c = std::net::TcpStream::connect(l.local_addr().unwrap()).unwrap();
+        let fd = c.into_raw_fd();
+        let e = unsafe { Listener::from_raw_fd_checked(fd) }.err().unwrap();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        // a rejected fd is left open and still ours
+        drop(unsafe { OwnedFd::from_raw_fd(fd) });
+    }
+    #[test]
+    fn accept_stream() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            let a = l.local_addr().unwrap();
+            let client = tokio::spawn(async move {
+                let mut c = Stream::connect(&a).await.unwrap();
+                c.write_all(b"x").await.unwrap();
+                let mut buf = [0u8; 1];
+                c.read_exact(&mut buf).await.unwrap();
+                buf
+            });
+            let mut s = l.accept_stream().await.unwrap();
+            let mut buf = [0u8; 1];
+            s.read_exact(&mut buf).await.unwrap();
+            s.write_all(&buf).await.unwrap();
+            assert_eq!(&client.await.unwrap(), b"x");
+        });
+    }
+    #[cfg(feature = "stream")]
+    #[test]
+    fn incoming() {
+        use futures_util::StreamExt;
+
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            let a = l.local_addr().unwrap();
+            let clients = tokio::spawn(async move {
+                let mut v = Vec::new();
+                for _ in 0..3 {
+                    v.push(Stream::connect(&a).await.unwrap());
+                }
+                v
+            });
+            let mut incoming = l.incoming();
+            for _ in 0..3 {
+                incoming.next().await.unwrap().unwrap();
+            }
+            assert_eq!(clients.await.unwrap().len(), 3);
+        });
+    }
+    #[test]
+    fn bind_any() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            // TEST-NET-1 is not assigned to any local interface
+            let addrs = ["192.0.2.1:0".parse().unwrap(), "127.0.0.1:0".parse().unwrap()];
+            let l = Listener::bind_any(&addrs).await.unwrap();
+            let a = l.local_addr().unwrap();
+            assert!(a.as_inet().unwrap().ip().is_loopback());
+
+            let e = Listener::bind_any(&addrs[..1]).await.err().unwrap();
+            assert_eq!(e.kind(), io::ErrorKind::AddrNotAvailable);
+            assert!(e.to_string().starts_with("192.0.2.1:0: "));
+            let e = Listener::bind_any(&[]).await.err().unwrap();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        });
+    }
+    #[test]
+    fn tcp_local_addr() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            let a = l.local_addr().unwrap();
+            assert_ne!(a.as_inet().unwrap().port(), 0);
+            Stream::connect(&a).await.unwrap();
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_local_addr() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let a: Addr = "/tmp/asc_local_addr.sock".parse().unwrap();
+            let _ = std::fs::remove_file("/tmp/asc_local_addr.sock");
+            let l = Listener::bind(&a).await.unwrap();
+            assert_eq!(l.local_addr().unwrap(), a);
+        });
+    }
+    #[test]
+    fn tcp_ttl() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            l.set_ttl(64).unwrap();
+            assert_eq!(l.ttl().unwrap(), 64);
+        });
+    }
+    #[cfg(all(target_os = "linux", feature = "unix"))]
+    #[test]
+    fn unix_abstract() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let a: Addr = format!("@my.service.{}", std::process::id()).parse().unwrap();
+            let l = Listener::bind(&a).await.unwrap();
+            let mut c = Stream::connect(&a).await.unwrap();
+            let (mut s, _) = l.accept().await.unwrap();
+            assert_eq!(s.local_addr().unwrap(), a);
+            c.write_all(b"1").await.unwrap();
+            let mut buf = [0u8; 1];
+            s.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"1");
+            drop(l);
+        });
+    }
+    #[test]
+    // without Unix sockets, there are only TCP listeners
+    #[allow(clippy::infallible_destructuring_match)]
+    fn std_tcp() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let a: Addr = l.local_addr().unwrap().into();
+            let l = Listener::from_std_tcp(l).unwrap();
+            let c = Stream::connect(&a).await.unwrap();
+            let (_, peer) = l.accept().await.unwrap();
+            assert_eq!(peer, c.local_addr().unwrap());
+
+            let l = match l.into_std().unwrap() {
+                StdListener::Inet(l) => l,
+                #[cfg(all(unix, feature = "unix"))]
+                _ => unreachable!(),
+            };
+            assert_eq!(Addr::from(l.local_addr().unwrap()), a);
+            let l = Listener::from_std_tcp(l).unwrap();
+            let c = Stream::connect(&a).await.unwrap();
+            let (_, peer) = l.accept().await.unwrap();
+            assert_eq!(peer, c.local_addr().unwrap());
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn std_unix() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let _ = std::fs::remove_file("/tmp/asc_listener_std.sock");
+            let l = std::os::unix::net::UnixListener::bind("/tmp/asc_listener_std.sock").unwrap();
+            let a: Addr = "/tmp/asc_listener_std.sock".parse().unwrap();
+            let l = Listener::from_std_unix(l).unwrap();
+            let _c = Stream::connect(&a).await.unwrap();
+            l.accept().await.unwrap();
+
+            let l = match l.into_std().unwrap() {
+                StdListener::Unix(l) => l,
+                _ => unreachable!(),
+            };
+            assert!(std::path::Path::new("/tmp/asc_listener_std.sock").exists());
+            let l = Listener::from_std_unix(l).unwrap();
+            let _c = Stream::connect(&a).await.unwrap();
+            l.accept().await.unwrap();
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn from_raw_fd_checked() {
+        use std::os::unix::io::{FromRawFd, IntoRawFd};
+
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let a: Addr = l.local_addr().unwrap().into();
+            let l = unsafe { Listener::from_raw_fd_checked(l.into_raw_fd()) }.unwrap();
+            assert!(matches!(l, Listener::Inet(_)));
+            let c = Stream::connect(&a).await.unwrap();
+            let (_, peer) = l.accept().await.unwrap();
+            assert_eq!(peer, c.local_addr().unwrap());
+
+            let _ = std::fs::remove_file("/tmp/asc_listener_fd.sock");
+            let l = std::os::unix::net::UnixListener::bind("/tmp/asc_listener_fd.sock").unwrap();
+            let a: Addr = "/tmp/asc_listener_fd.sock".parse().unwrap();
+            let l = unsafe { Listener::from_raw_fd_checked(l.into_raw_fd()) }.unwrap();
+            assert!(matches!(l, Listener::Unix(_, None, false)));
+            let _c = Stream::connect(&a).await.unwrap();
+            l.accept().await.unwrap();
+
+            let u = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            let fd = u.into_raw_fd();
+            let e = unsafe { Listener::from_raw_fd_checked(fd) }.err().unwrap();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+            // still open and owned by us
+            drop(unsafe { std::net::UdpSocket::from_raw_fd(fd) });
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn drop_removed_file() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let _ = std::fs::remove_file("/tmp/asc_listener_gone.sock");
+            let l = Listener::bind(&"/tmp/asc_listener_gone.sock".parse().unwrap())
+                .await
+                .unwrap();
+            std::fs::remove_file("/tmp/asc_listener_gone.sock").unwrap();
+            drop(l);
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn drop_keeps_inherited_file() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let _ = std::fs::remove_file("/tmp/asc_listener_keep.sock");
+            let l = std::os::unix::net::UnixListener::bind("/tmp/asc_listener_keep.sock").unwrap();
+            let l = Listener::from_std_unix(l).unwrap();
+            drop(l);
+            assert!(std::path::Path::new("/tmp/asc_listener_keep.sock").exists());
+
+            let l = std::os::unix::net::UnixListener::bind("/tmp/asc_listener_keep.sock");
+            assert!(l.is_err(), "file is still there");
+            std::fs::remove_file("/tmp/asc_listener_keep.sock").unwrap();
+            let l = std::os::unix::net::UnixListener::bind("/tmp/asc_listener_keep.sock").unwrap();
+            let mut l = Listener::from_std_unix(l).unwrap();
+            l.set_unlink_on_drop(true);
+            drop(l);
+            assert!(!std::path::Path::new("/tmp/asc_listener_keep.sock").exists());
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn accept_with_cred() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let path = "/tmp/asc_listener_cred.sock";
+            let _ = std::fs::remove_file(path);
+            let l = Listener::bind(&path.parse().unwrap()).await.unwrap();
+            let (c, s) = tokio::join!(Stream::connect_to_listener(&l), l.accept_with_cred());
+            c.unwrap();
+            let cred = s.unwrap().2.unwrap();
+            assert_eq!(cred.uid(), unsafe { libc::getuid() });
+
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            let (c, s) = tokio::join!(Stream::connect_to_listener(&l), l.accept_with_cred());
+            c.unwrap();
+            assert!(s.unwrap().2.is_none());
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn toggle_unlink_on_drop() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let path = std::path::Path::new("/tmp/asc_listener_toggle.sock");
+            let _ = std::fs::remove_file(path);
+            let mut l = Listener::bind(&Addr::Unix(path.into())).await.unwrap();
+            l.set_unlink_on_drop(false);
+            l.set_unlink_on_drop(true);
+            // changed our mind again, e.g. because a replacement process is coming
+            l.set_unlink_on_drop(false);
+            drop(l);
+            assert!(path.exists());
+            std::fs::remove_file(path).unwrap();
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn reenable_unlink_on_drop() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let path = std::path::Path::new("/tmp/asc_listener_reenable.sock");
+            let _ = std::fs::remove_file(path);
+            let mut l = crate::ListenerBuilder::new().bind_unix_atomic(path).unwrap();
+            l.set_unlink_on_drop(false);
+            assert_eq!(l.local_addr().unwrap(), Addr::Unix(path.into()));
+            l.set_unlink_on_drop(true);
+            drop(l);
+            assert!(!path.exists());
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn leak_socket() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let path = std::path::Path::new("/tmp/asc_listener_leak.sock");
+            let _ = std::fs::remove_file(path);
+            let l = Listener::bind(&Addr::Unix(path.into())).await.unwrap();
+            let (l, leaked) = l.leak_socket();
+            assert_eq!(leaked.as_deref(), Some(path));
+            drop(l);
+            assert!(path.exists());
+            std::fs::remove_file(path).unwrap();
+
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            assert!(l.leak_socket().1.is_none());
+        });
     }
-}
\ No newline at end of file
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn drop_removes_file() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let _ = std::fs::remove_file("/tmp/asc_listener_drop.sock");
+            let l = Listener::bind(&"/tmp/asc_listener_drop.sock".parse().unwrap())
+                .await
+                .unwrap();
+            assert!(std::path::Path::new("/tmp/asc_listener_drop.sock").exists());
+            drop(l);
+            assert!(!std::path::Path::new("/tmp/asc_listener_drop.sock").exists());
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_ttl() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let _ = std::fs::remove_file("/tmp/asc_listener_ttl.sock");
+            let l = Listener::bind(&"/tmp/asc_listener_ttl.sock".parse().unwrap())
+                .await
+                .unwrap();
+            assert_eq!(l.set_ttl(64).unwrap_err().kind(), io::ErrorKind::Unsupported);
+            assert_eq!(l.ttl().unwrap_err().kind(), io::ErrorKind::Unsupported);
+        });
+    }
+}
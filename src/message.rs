@@ -0,0 +1,152 @@
+use std::io;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::Stream;
+
+impl Stream {
+    /// Reads a message written by [`Stream::write_message`].
+    ///
+    /// A message is a big-endian `u32` length followed by that many bytes.
+    /// Lengths over `max` are rejected with an error of kind [`io::ErrorKind::InvalidData`],
+    /// before anything is allocated. The stream is out of sync after such an error.
+    pub async fn read_message(&mut self, max: usize) -> io::Result<Vec<u8>> {
+        let len = self.read_u32().await? as usize;
+        if len > max {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("message of {} bytes exceeds the limit of {}", len, max),
+            ));
+        }
+        let mut msg = vec![0; len];
+        self.read_exact(&mut msg).await?;
+        Ok(msg)
+    }
+    /// Reads until EOF, like [`read_to_end`](tokio::io::AsyncReadExt::read_to_end),
+    /// but at most `max` bytes.
+    ///
+    /// If the peer sends more than `max` bytes, an error of kind [`io::ErrorKind::InvalidData`]
+    /// is returned. Up to one byte more than `max` is consumed from the stream in that case.
+    pub async fn read_to_end_limited(&mut self, max: usize) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        let limit = (max as u64).saturating_add(1);
+        (&mut *self).take(limit).read_to_end(&mut buf).await?;
+        if buf.len() > max {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("data exceeds the limit of {} bytes", max),
+            ));
+        }
+        Ok(buf)
+    }
+    /// Writes `req`, shuts down the write half and reads the response until EOF.
+    ///
+    /// This suits simple protocols where the peer only answers once the request is complete.
+    /// The response is limited to `max_resp` bytes like in [`Stream::read_to_end_limited`].
+    pub async fn request_response(&mut self, req: &[u8], max_resp: usize) -> io::Result<Vec<u8>> {
+        self.write_all(req).await?;
+        self.shutdown().await?;
+        self.read_to_end_limited(max_resp).await
+    }
+    /// Reads and discards data until EOF, returning how many bytes were discarded.
+    ///
+    /// This consumes the rest of an abandoned request, so that closing the connection
+    /// does not reset it. If the peer sends more than `max` bytes, an error of kind
+    /// [`io::ErrorKind::InvalidData`] is returned.
+    pub async fn drain(&mut self, max: usize) -> io::Result<usize> {
+        let limit = (max as u64).saturating_add(1);
+        let n = tokio::io::copy(&mut (&mut *self).take(limit), &mut tokio::io::sink()).await?;
+        if n > max as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("data exceeds the limit of {} bytes", max),
+            ));
+        }
+        Ok(n as usize)
+    }
+    /// Writes `msg` prefixed by its length as a big-endian `u32`.
+    ///
+    /// Messages longer than `u32::MAX` return an error of kind [`io::ErrorKind::InvalidInput`].
+    pub async fn write_message(&mut self, msg: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(msg.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "message too long"))?;
+        self.write_all(&len.to_be_bytes()).await?;
+        self.write_all(msg).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::tests::tcp_pair;
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn round_trip() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            c.write_message(b"hello").await.unwrap();
+            c.write_message(b"").await.unwrap();
+            assert_eq!(s.read_message(5).await.unwrap(), b"hello");
+            assert_eq!(s.read_message(5).await.unwrap(), b"");
+        });
+    }
+    #[test]
+    fn oversize() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            c.write_all(&u32::MAX.to_be_bytes()).await.unwrap();
+            let e = s.read_message(1024).await.unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        });
+    }
+    #[test]
+    fn read_to_end_limited() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            c.write_all(b"body").await.unwrap();
+            c.shutdown().await.unwrap();
+            assert_eq!(s.read_to_end_limited(4).await.unwrap(), b"body");
+
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            c.write_all(b"too long").await.unwrap();
+            c.shutdown().await.unwrap();
+            let e = s.read_to_end_limited(4).await.unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        });
+    }
+    #[test]
+    fn request_response() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            let server = tokio::spawn(async move {
+                let mut req = Vec::new();
+                s.read_to_end(&mut req).await.unwrap();
+                s.write_all(&req).await.unwrap();
+                s.write_all(&req).await.unwrap();
+            });
+            let resp = c.request_response(&[7; 5000], 10000).await.unwrap();
+            assert_eq!(resp, [7; 10000]);
+            server.await.unwrap();
+        });
+    }
+    #[test]
+    fn drain() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            c.write_all(&[1; 1000]).await.unwrap();
+            c.shutdown().await.unwrap();
+            assert_eq!(s.drain(1000).await.unwrap(), 1000);
+
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            c.write_all(&[1; 1000]).await.unwrap();
+            c.shutdown().await.unwrap();
+            let e = s.drain(999).await.unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        });
+    }
+}
@@ -0,0 +1,115 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, Error, ReadBuf};
+
+use crate::Stream;
+
+/// A [`Stream`] with bytes pushed back in front of it, created by [`Stream::with_prefix`].
+///
+/// Reading first drains the prefix, then continues with the socket.
+/// Writes go to the socket directly.
+#[derive(Debug)]
+pub struct PeekableStream {
+    prefix: Vec<u8>,
+    pos: usize,
+    inner: Stream,
+}
+
+impl Stream {
+    /// Pushes `buf` back in front of this stream.
+    ///
+    /// This allows protocol sniffers to hand on bytes they already read.
+    pub fn with_prefix(self, buf: Vec<u8>) -> PeekableStream {
+        PeekableStream {
+            prefix: buf,
+            pos: 0,
+            inner: self,
+        }
+    }
+}
+
+impl PeekableStream {
+    /// Returns the bytes of the prefix that have not been read yet.
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix[self.pos..]
+    }
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &Stream {
+        &self.inner
+    }
+    /// Gets a mutable reference to the underlying stream.
+    ///
+    /// Reading from it directly skips the remaining prefix.
+    pub fn get_mut(&mut self) -> &mut Stream {
+        &mut self.inner
+    }
+    /// Returns the underlying stream and the bytes of the prefix that have not been read yet.
+    pub fn into_parts(mut self) -> (Stream, Vec<u8>) {
+        self.prefix.drain(..self.pos);
+        (self.inner, self.prefix)
+    }
+}
+
+impl AsyncRead for PeekableStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        let this = &mut *self;
+        if this.pos < this.prefix.len() {
+            let n = buf.remaining().min(this.prefix.len() - this.pos);
+            buf.put_slice(&this.prefix[this.pos..this.pos + n]);
+            this.pos += n;
+            if this.pos == this.prefix.len() {
+                this.prefix = Vec::new();
+                this.pos = 0;
+            }
+            return Poll::Ready(Ok(()));
+        }
+        Pin::new(&mut this.inner).poll_read(cx, buf)
+    }
+}
+impl AsyncWrite for PeekableStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stream::tests::tcp_pair;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn prefix_first() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, s) = tcp_pair().await.unwrap();
+            c.write_all(b"5678").await.unwrap();
+            drop(c);
+            let mut s = s.with_prefix(b"1234".to_vec());
+            assert_eq!(s.prefix(), b"1234");
+            let mut buf = [0u8; 2];
+            s.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"12");
+            assert_eq!(s.prefix(), b"34");
+            let mut buf = Vec::new();
+            s.read_to_end(&mut buf).await.unwrap();
+            assert_eq!(buf, b"345678");
+        });
+    }
+}
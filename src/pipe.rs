@@ -0,0 +1,88 @@
+use tokio::net::windows::named_pipe::{NamedPipeServer, ServerOptions};
+
+use std::io;
+use tokio::sync::Mutex;
+
+/// A Windows named pipe server, listening for connections.
+///
+/// A named pipe has one instance per connection.
+/// There is always one instance waiting for the next client.
+#[derive(Debug)]
+pub struct PipeListener {
+    name: String,
+    next: Mutex<NamedPipeServer>,
+}
+
+impl PipeListener {
+    /// Creates the first instance of the pipe `name`.
+    ///
+    /// Fails if the pipe already exists.
+    pub(crate) fn bind(name: &str) -> io::Result<PipeListener> {
+        let first = ServerOptions::new().first_pipe_instance(true).create(name)?;
+        Ok(PipeListener {
+            name: name.to_string(),
+            next: Mutex::new(first),
+        })
+    }
+    /// Name of the pipe, like `\\.\pipe\name`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Waits for a client to connect to the pipe.
+    ///
+    /// This is cancel safe: the waiting instance stays in place
+    /// and is only replaced once a client connected to it.
+    pub(crate) async fn accept(&self) -> io::Result<NamedPipeServer> {
+        let mut next = self.next.lock().await;
+        next.connect().await?;
+        let new = ServerOptions::new().create(&self.name)?;
+        Ok(std::mem::replace(&mut *next, new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Addr, Listener, Stream};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn pipe_echo() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let a: Addr = format!(r"\\.\pipe\asc-test-{}", std::process::id())
+                .parse()
+                .unwrap();
+            let l = Listener::bind(&a).await.unwrap();
+            let server = tokio::spawn(async move {
+                let (mut s, peer) = l.accept().await.unwrap();
+                assert!(matches!(peer, Addr::Pipe(_)));
+                let mut buf = [0u8; 1];
+                s.read_exact(&mut buf).await.unwrap();
+                s.write_all(&buf).await.unwrap();
+            });
+            let mut c = Stream::connect(&a).await.unwrap();
+            c.write_all(b"1").await.unwrap();
+            let mut buf = [0u8; 1];
+            c.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"1");
+            server.await.unwrap();
+        });
+    }
+    #[test]
+    fn cancelled_accept() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let a: Addr = format!(r"\\.\pipe\asc-cancel-{}", std::process::id())
+                .parse()
+                .unwrap();
+            let l = Listener::bind(&a).await.unwrap();
+            let r = tokio::time::timeout(std::time::Duration::from_millis(10), l.accept()).await;
+            assert!(r.is_err());
+            // the waiting instance survived, so the client can connect to it
+            let (c, s) = tokio::join!(Stream::connect(&a), l.accept());
+            c.unwrap();
+            s.unwrap();
+        });
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, Error, ReadBuf};
+use tokio::time::Instant;
+
+use crate::{Addr, Stream};
+
+/// An idle connection and since when it is idle
+struct Idle {
+    stream: Stream,
+    since: Instant,
+}
+
+type Connections = Arc<Mutex<HashMap<Addr, Vec<Idle>>>>;
+
+/// Reuses connections to the same [`Addr`].
+///
+/// Streams handed out by [`Pool::get`] go back to the pool when they are dropped,
+/// unless an error or EOF was seen on them, or data is left unread.
+/// Idle connections are closed after the idle timeout, if the peer closed them
+/// or if it sent data nobody asked for.
+/// Clones share the same connections.
+/// ```
+/// # use async_stream_connection::{Addr, Pool};
+/// # async fn doc(backend: Addr) -> std::io::Result<()> {
+/// let pool = Pool::new();
+/// let s = pool.get(&backend).await?;
+/// // ... use it, then drop it to put it back
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Pool {
+    idle: Connections,
+    max_idle: usize,
+    idle_timeout: Duration,
+}
+
+impl Default for Pool {
+    fn default() -> Self {
+        Pool {
+            idle: Arc::default(),
+            max_idle: 8,
+            idle_timeout: Duration::from_secs(90),
+        }
+    }
+}
+
+impl Pool {
+    /// Creates a pool keeping up to 8 idle connections per address, for 90s at most.
+    pub fn new() -> Pool {
+        Pool::default()
+    }
+    /// Sets how many idle connections are kept per address.
+    pub fn max_idle(mut self, max: usize) -> Pool {
+        self.max_idle = max;
+        self
+    }
+    /// Sets how long a connection is kept while it is not used.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Pool {
+        self.idle_timeout = timeout;
+        self
+    }
+    /// Returns an idle connection to `addr` or opens a new one.
+    pub async fn get(&self, addr: &Addr) -> io::Result<PooledStream> {
+        let reused = {
+            let mut idle = self.idle.lock().unwrap();
+            let mut found = None;
+            if let Some(list) = idle.get_mut(addr) {
+                // the most recently used connection is the most likely to be alive
+                while let Some(i) = list.pop() {
+                    if i.since.elapsed() < self.idle_timeout && is_alive(&i.stream) {
+                        found = Some(i.stream);
+                        break;
+                    }
+                }
+                if list.is_empty() {
+                    idle.remove(addr);
+                }
+            }
+            found
+        };
+        let stream = match reused {
+            Some(s) => s,
+            None => Stream::connect(addr).await?,
+        };
+        Ok(PooledStream {
+            stream: Some(stream),
+            addr: addr.clone(),
+            pool: self.clone(),
+            healthy: true,
+        })
+    }
+    /// Takes back a connection, dropping it if there are enough already
+    fn put(&self, addr: Addr, stream: Stream) {
+        let mut idle = self.idle.lock().unwrap();
+        let list = idle.entry(addr).or_default();
+        list.retain(|i| i.since.elapsed() < self.idle_timeout);
+        if list.len() < self.max_idle {
+            list.push(Idle {
+                stream,
+                since: Instant::now(),
+            });
+        }
+    }
+}
+
+impl fmt::Debug for Pool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("max_idle", &self.max_idle)
+            .field("idle_timeout", &self.idle_timeout)
+            .finish()
+    }
+}
+
+/// The peer did not close the connection and there is no unread data left, as far as we can tell
+fn is_alive(s: &Stream) -> bool {
+    match s.is_idle() {
+        Ok(idle) => idle,
+        Err(crate::Error::Unsupported { .. }) => true,
+        Err(crate::Error::Io(_)) => false,
+    }
+}
+
+/// A [`Stream`] borrowed from a [`Pool`], created by [`Pool::get`].
+///
+/// It goes back to the pool on drop, unless reading or writing failed, reading hit EOF,
+/// it was shut down or there is unread data left, like the rest of a response.
+pub struct PooledStream {
+    /// Always `Some` until dropped or detached
+    stream: Option<Stream>,
+    addr: Addr,
+    pool: Pool,
+    healthy: bool,
+}
+
+impl PooledStream {
+    /// The address of the connection
+    pub fn addr(&self) -> &Addr {
+        &self.addr
+    }
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &Stream {
+        self.stream.as_ref().expect("stream present")
+    }
+    /// Takes the stream out of the pool for good.
+    pub fn detach(mut self) -> Stream {
+        self.stream.take().expect("stream present")
+    }
+    fn stream(&mut self) -> Pin<&mut Stream> {
+        Pin::new(self.stream.as_mut().expect("stream present"))
+    }
+    /// Marks the connection as unusable if `r` is an error
+    fn check<T>(&mut self, r: Poll<io::Result<T>>) -> Poll<io::Result<T>> {
+        if let Poll::Ready(Err(_)) = r {
+            self.healthy = false;
+        }
+        r
+    }
+}
+
+impl Drop for PooledStream {
+    fn drop(&mut self) {
+        if let Some(s) = self.stream.take() {
+            if self.healthy && is_alive(&s) {
+                self.pool.put(self.addr.clone(), s);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for PooledStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledStream")
+            .field("addr", &self.addr)
+            .field("stream", &self.stream)
+            .finish()
+    }
+}
+
+impl AsyncRead for PooledStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        let before = buf.filled().len();
+        let r = self.stream().poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = r {
+            if buf.filled().len() == before && buf.remaining() > 0 {
+                // EOF
+                self.healthy = false;
+            }
+        }
+        self.check(r)
+    }
+}
+impl AsyncWrite for PooledStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        let r = self.stream().poll_write(cx, buf);
+        self.check(r)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<Result<usize, Error>> {
+        let r = self.stream().poll_write_vectored(cx, bufs);
+        self.check(r)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.get_ref().is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        let r = self.stream().poll_flush(cx);
+        self.check(r)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        // a connection that is shut down can't be reused
+        self.healthy = false;
+        self.stream().poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::tests::local_socket_pair;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn reuse() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (l, a) = local_socket_pair().await.unwrap();
+            let server = tokio::spawn(async move {
+                let (mut s, _) = l.accept().await.unwrap();
+                let mut buf = [0u8; 2];
+                s.read_exact(&mut buf).await.unwrap();
+                s
+            });
+            let pool = Pool::new();
+            let mut c = pool.get(&a).await.unwrap();
+            let first = c.get_ref().local_addr().unwrap();
+            c.write_all(b"hi").await.unwrap();
+            drop(c);
+            let c = pool.get(&a).await.unwrap();
+            assert_eq!(c.get_ref().local_addr().unwrap(), first);
+
+            // a connection closed by the peer is not kept
+            drop(server.await.unwrap());
+            c.get_ref().readable().await.unwrap();
+            drop(c);
+            assert!(pool.idle.lock().unwrap().get(&a).is_none());
+        });
+    }
+    #[test]
+    fn unread_response() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (l, a) = local_socket_pair().await.unwrap();
+            let server = tokio::spawn(async move {
+                let (mut s, _) = l.accept().await.unwrap();
+                s.write_all(b"response").await.unwrap();
+                s
+            });
+            let pool = Pool::new();
+            let c = pool.get(&a).await.unwrap();
+            let _s = server.await.unwrap();
+            c.get_ref().readable().await.unwrap();
+            // the response is left unread
+            drop(c);
+            assert!(pool.idle.lock().unwrap().get(&a).is_none());
+        });
+    }
+}
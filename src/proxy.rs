@@ -0,0 +1,280 @@
+use tokio::io::AsyncReadExt;
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use crate::{Addr, Stream};
+
+/// Signature of a version 1 (text) header
+const V1_SIG: &[u8] = b"PROXY ";
+/// A version 1 header is at most this long, including the CRLF
+const V1_MAX_LEN: usize = 107;
+/// Signature of a version 2 (binary) header
+const V2_SIG: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+/// Fixed part of a version 2 header
+const V2_HEADER_LEN: usize = 16;
+/// First pause before peeking again, if the data is too short to tell whether there is a header
+const RECHECK_DELAY: Duration = Duration::from_millis(1);
+/// The pause doubles up to this
+const MAX_RECHECK_DELAY: Duration = Duration::from_millis(50);
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// `Some(true)` if `data` starts with `sig`,
+/// `None` if `data` is too short to tell
+fn has_signature(data: &[u8], sig: &[u8]) -> Option<bool> {
+    let common = data.len().min(sig.len());
+    if data[..common] != sig[..common] {
+        Some(false)
+    } else if data.len() < sig.len() {
+        None
+    } else {
+        Some(true)
+    }
+}
+
+impl Stream {
+    /// Reads a PROXY protocol (version 1 or 2) header from the front of the stream,
+    /// as sent by load balancers such as HAProxy or AWS NLB.
+    ///
+    /// Returns the stream positioned right after the header and the original source address.
+    /// The address is `None` if the header does not carry one (`LOCAL` / `UNKNOWN`) or if there is no header at all.
+    /// In the latter case, no bytes are consumed.
+    ///
+    /// The header is inspected using [`Stream::peek`], so it must arrive at once, as the PROXY protocol specification demands.
+    /// A partial header results in an error of kind [`io::ErrorKind::InvalidData`].
+    /// If the first bytes could still be the start of a signature (like a lone `P`),
+    /// this waits until more data arrives. Apply a timeout if clients might stop there.
+    pub async fn accept_proxy_protocol(mut self) -> io::Result<(Stream, Option<Addr>)> {
+        let mut buf = vec![0u8; 256];
+        let mut delay = RECHECK_DELAY;
+        let (n, v1, v2) = loop {
+            let n = self.peek(&mut buf).await?;
+            if n == 0 {
+                return Ok((self, None));
+            }
+            let data = &buf[..n];
+            if let (Some(v1), Some(v2)) = (has_signature(data, V1_SIG), has_signature(data, V2_SIG)) {
+                break (n, v1, v2);
+            }
+            // peeking again returns right away, so wait before looking for more data
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RECHECK_DELAY);
+        };
+        let data = &buf[..n];
+        let (len, addr) = if v1 {
+            parse_v1(data)?
+        } else if v2 {
+            if n < V2_HEADER_LEN {
+                return Err(invalid("incomplete PROXY header"));
+            }
+            let len = V2_HEADER_LEN + u16::from_be_bytes([data[14], data[15]]) as usize;
+            if len > n {
+                buf.resize(len, 0);
+                if self.peek(&mut buf).await? < len {
+                    return Err(invalid("incomplete PROXY header"));
+                }
+            }
+            (len, parse_v2(&buf[..len])?)
+        } else {
+            return Ok((self, None));
+        };
+        // consume the header
+        self.read_exact(&mut buf[..len]).await?;
+        Ok((self, addr))
+    }
+}
+
+/// Parses `PROXY TCP4 1.2.3.4 5.6.7.8 1234 80\r\n`
+fn parse_v1(data: &[u8]) -> io::Result<(usize, Option<Addr>)> {
+    let end = data[..data.len().min(V1_MAX_LEN)]
+        .windows(2)
+        .position(|w| w == b"\r\n")
+        .ok_or_else(|| invalid("incomplete PROXY header"))?;
+    let line = std::str::from_utf8(&data[..end]).map_err(|_| invalid("invalid PROXY header"))?;
+    let mut parts = line.split(' ').skip(1);
+    let addr = match parts.next() {
+        Some("UNKNOWN") => None,
+        Some("TCP4") | Some("TCP6") => {
+            let src: IpAddr = parts
+                .next()
+                .and_then(|p| p.parse().ok())
+                .ok_or_else(|| invalid("invalid PROXY source address"))?;
+            let _dst = parts.next();
+            let port: u16 = parts
+                .next()
+                .and_then(|p| p.parse().ok())
+                .ok_or_else(|| invalid("invalid PROXY source port"))?;
+            Some(Addr::Inet(SocketAddr::new(src, port)))
+        }
+        _ => return Err(invalid("invalid PROXY protocol")),
+    };
+    Ok((end + 2, addr))
+}
+
+/// Parses the binary header. `data` is exactly the header.
+fn parse_v2(data: &[u8]) -> io::Result<Option<Addr>> {
+    let ver_cmd = data[12];
+    if ver_cmd >> 4 != 2 {
+        return Err(invalid("unsupported PROXY version"));
+    }
+    match ver_cmd & 0xF {
+        // LOCAL: health checks of the proxy itself
+        0 => return Ok(None),
+        1 => {}
+        _ => return Err(invalid("invalid PROXY command")),
+    }
+    let addrs = &data[V2_HEADER_LEN..];
+    let addr = match data[13] >> 4 {
+        // AF_INET
+        1 if addrs.len() >= 12 => {
+            let ip = Ipv4Addr::new(addrs[0], addrs[1], addrs[2], addrs[3]);
+            let port = u16::from_be_bytes([addrs[8], addrs[9]]);
+            Some(Addr::Inet(SocketAddr::new(ip.into(), port)))
+        }
+        // AF_INET6
+        2 if addrs.len() >= 36 => {
+            let mut ip = [0u8; 16];
+            ip.copy_from_slice(&addrs[..16]);
+            let port = u16::from_be_bytes([addrs[32], addrs[33]]);
+            Some(Addr::Inet(SocketAddr::new(Ipv6Addr::from(ip).into(), port)))
+        }
+        // AF_UNIX
+        3 if addrs.len() >= 216 => unix_addr(&addrs[..108]),
+        1..=3 => return Err(invalid("invalid PROXY address length")),
+        _ => None,
+    };
+    Ok(addr)
+}
+
+/// The source of an AF_UNIX block, if it has one
+#[cfg(all(unix, feature = "unix"))]
+fn unix_addr(path: &[u8]) -> Option<Addr> {
+    use std::os::unix::ffi::OsStrExt;
+    let path = match path.iter().position(|&b| b == 0) {
+        Some(end) => &path[..end],
+        None => path,
+    };
+    if path.is_empty() {
+        None
+    } else {
+        Some(Addr::Unix(std::ffi::OsStr::from_bytes(path).into()))
+    }
+}
+/// Without Unix sockets, the source can't be represented
+#[cfg(not(all(unix, feature = "unix")))]
+fn unix_addr(_path: &[u8]) -> Option<Addr> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::tests::tcp_pair;
+    use tokio::io::AsyncWriteExt;
+    use tokio::runtime::Builder;
+
+    async fn proxied(header: &[u8]) -> Option<Addr> {
+        let (mut c, s) = tcp_pair().await.unwrap();
+        let mut data = header.to_vec();
+        data.extend_from_slice(b"GET /");
+        c.write_all(&data).await.unwrap();
+        let (mut s, addr) = s.accept_proxy_protocol().await.unwrap();
+        let mut buf = [0u8; 5];
+        s.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"GET /");
+        addr
+    }
+
+    #[test]
+    fn v1_tcp4() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let a = proxied(b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\n").await;
+            assert_eq!(a, Some("192.168.0.1:56324".parse().unwrap()));
+            let a = proxied(b"PROXY TCP6 ::1 ::2 56324 443\r\n").await;
+            assert_eq!(a, Some("[::1]:56324".parse().unwrap()));
+            let a = proxied(b"PROXY UNKNOWN\r\n").await;
+            assert_eq!(a, None);
+        });
+    }
+    #[test]
+    fn v2_binary() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let mut h = V2_SIG.to_vec();
+            // PROXY, TCP over IPv4, 12 bytes of addresses
+            h.extend_from_slice(&[0x21, 0x11, 0, 12]);
+            h.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2]);
+            h.extend_from_slice(&1234u16.to_be_bytes());
+            h.extend_from_slice(&443u16.to_be_bytes());
+            let a = proxied(&h).await;
+            assert_eq!(a, Some("10.0.0.1:1234".parse().unwrap()));
+
+            let mut h = V2_SIG.to_vec();
+            // LOCAL with a TLV that is skipped
+            h.extend_from_slice(&[0x20, 0x00, 0, 3, 0x04, 0, 0]);
+            assert_eq!(proxied(&h).await, None);
+        });
+    }
+    #[test]
+    fn v2_unix() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let mut h = V2_SIG.to_vec();
+            // PROXY, UNIX stream, 216 bytes of addresses
+            h.extend_from_slice(&[0x21, 0x31, 0, 216]);
+            let mut src = [0u8; 108];
+            src[..13].copy_from_slice(b"/tmp/src.sock");
+            h.extend_from_slice(&src);
+            h.extend_from_slice(&[0u8; 108]);
+            let a = proxied(&h).await;
+            #[cfg(all(unix, feature = "unix"))]
+            assert_eq!(a, Some(Addr::Unix("/tmp/src.sock".into())));
+            #[cfg(not(all(unix, feature = "unix")))]
+            assert_eq!(a, None);
+
+            // an AF_UNIX block that is too short
+            let mut h = V2_SIG.to_vec();
+            h.extend_from_slice(&[0x21, 0x31, 0, 12]);
+            h.extend_from_slice(&[0u8; 12]);
+            h.extend_from_slice(b"GET /");
+            let (mut c, s) = tcp_pair().await.unwrap();
+            c.write_all(&h).await.unwrap();
+            let e = s.accept_proxy_protocol().await.unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        });
+    }
+    #[test]
+    fn no_header() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            assert_eq!(proxied(b"").await, None);
+        });
+    }
+    #[test]
+    fn short_first_write() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            for first in [&b"P"[..], b"\r\n"] {
+                let (mut c, s) = tcp_pair().await.unwrap();
+                c.write_all(first).await.unwrap();
+                let client = tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    c.write_all(b"OST /").await.unwrap();
+                    c
+                });
+                let (mut s, addr) = s.accept_proxy_protocol().await.unwrap();
+                assert_eq!(addr, None);
+                let mut buf = vec![0u8; first.len() + 5];
+                s.read_exact(&mut buf).await.unwrap();
+                assert_eq!(&buf[..first.len()], first);
+                assert_eq!(&buf[first.len()..], b"OST /");
+                client.await.unwrap();
+            }
+        });
+    }
+}
@@ -0,0 +1,270 @@
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, Error, ReadBuf};
+
+use crate::{Addr, Stream};
+
+/// Delays between the attempts of a [`ReconnectStream`].
+///
+/// The delay starts at `initial_delay` and doubles with every failed attempt, up to `max_delay`.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial_delay: Duration,
+    max_delay: Duration,
+    attempts: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            attempts: 5,
+        }
+    }
+}
+
+impl Backoff {
+    /// Creates a policy of 5 attempts, starting with a delay of 100ms and waiting 10s at most.
+    pub fn new() -> Backoff {
+        Backoff::default()
+    }
+    /// Sets the delay before the first attempt.
+    pub fn initial_delay(mut self, delay: Duration) -> Backoff {
+        self.initial_delay = delay;
+        self
+    }
+    /// Sets the maximum delay between two attempts.
+    pub fn max_delay(mut self, delay: Duration) -> Backoff {
+        self.max_delay = delay;
+        self
+    }
+    /// Sets how often to try reconnecting before giving up.
+    pub fn attempts(mut self, attempts: u32) -> Backoff {
+        self.attempts = attempts;
+        self
+    }
+    /// Delay before attempt number `n`, counted from 0
+    fn delay(&self, n: u32) -> Duration {
+        self.initial_delay
+            .saturating_mul(1 << n.min(31))
+            .min(self.max_delay)
+    }
+}
+
+enum State {
+    Connected(Stream),
+    Connecting(Pin<Box<dyn Future<Output = io::Result<Stream>> + Send>>),
+}
+
+/// A client [`Stream`] that reconnects to its address if the connection is lost.
+///
+/// A lost connection (an I/O error or EOF) is replaced by a new one, following the [`Backoff`] policy.
+/// The counter of attempts is reset as soon as data is transferred.
+/// If all attempts fail, the last error (or EOF) is returned.
+///
+/// This is best effort: nothing is replayed.
+/// Data written to the old connection but not received by the peer is lost,
+/// and a protocol might be in the middle of a message when the connection is swapped.
+pub struct ReconnectStream {
+    addr: Addr,
+    backoff: Backoff,
+    failures: u32,
+    state: State,
+}
+
+impl ReconnectStream {
+    /// Opens a connection to `addr`.
+    ///
+    /// The first connection is not retried.
+    pub async fn connect(addr: Addr, backoff: Backoff) -> io::Result<ReconnectStream> {
+        let s = Stream::connect(&addr).await?;
+        Ok(ReconnectStream {
+            addr,
+            backoff,
+            failures: 0,
+            state: State::Connected(s),
+        })
+    }
+    /// The address connections are made to
+    pub fn addr(&self) -> &Addr {
+        &self.addr
+    }
+    /// Returns the current connection, if there is one.
+    pub fn get_ref(&self) -> Option<&Stream> {
+        match &self.state {
+            State::Connected(s) => Some(s),
+            State::Connecting(_) => None,
+        }
+    }
+    /// Starts the next attempt. `false` if there are none left.
+    fn reconnect(&mut self) -> bool {
+        if self.failures >= self.backoff.attempts {
+            return false;
+        }
+        let delay = self.backoff.delay(self.failures);
+        self.failures += 1;
+        let addr = self.addr.clone();
+        self.state = State::Connecting(Box::pin(async move {
+            tokio::time::sleep(delay).await;
+            Stream::connect(&addr).await
+        }));
+        true
+    }
+    /// Waits for a connection
+    fn poll_stream(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<&mut Stream>> {
+        while let State::Connecting(f) = &mut self.state {
+            match ready!(f.as_mut().poll(cx)) {
+                Ok(s) => self.state = State::Connected(s),
+                Err(e) => {
+                    if !self.reconnect() {
+                        return Poll::Ready(Err(e));
+                    }
+                }
+            }
+        }
+        match &mut self.state {
+            State::Connected(s) => Poll::Ready(Ok(s)),
+            State::Connecting(_) => unreachable!(),
+        }
+    }
+}
+
+impl fmt::Debug for ReconnectStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReconnectStream")
+            .field("addr", &self.addr)
+            .field("backoff", &self.backoff)
+            .field("stream", &self.get_ref())
+            .finish()
+    }
+}
+
+impl AsyncRead for ReconnectStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        let before = buf.filled().len();
+        loop {
+            let s = ready!(self.poll_stream(cx))?;
+            match ready!(Pin::new(s).poll_read(cx, buf)) {
+                Ok(()) if buf.filled().len() > before => {
+                    self.failures = 0;
+                    return Poll::Ready(Ok(()));
+                }
+                Ok(()) if buf.remaining() == 0 => return Poll::Ready(Ok(())),
+                // EOF
+                Ok(()) => {
+                    if !self.reconnect() {
+                        return Poll::Ready(Ok(()));
+                    }
+                }
+                Err(e) => {
+                    if !self.reconnect() {
+                        return Poll::Ready(Err(e));
+                    }
+                }
+            }
+        }
+    }
+}
+impl AsyncWrite for ReconnectStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        loop {
+            let s = ready!(self.poll_stream(cx))?;
+            match ready!(Pin::new(s).poll_write(cx, buf)) {
+                Ok(n) => {
+                    if n > 0 {
+                        self.failures = 0;
+                    }
+                    return Poll::Ready(Ok(n));
+                }
+                Err(e) => {
+                    if !self.reconnect() {
+                        return Poll::Ready(Err(e));
+                    }
+                }
+            }
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        let s = ready!(self.poll_stream(cx))?;
+        Pin::new(s).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        let s = ready!(self.poll_stream(cx))?;
+        Pin::new(s).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::tests::local_socket_pair;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn backoff() {
+        let b = Backoff::new()
+            .initial_delay(Duration::from_secs(1))
+            .max_delay(Duration::from_secs(5));
+        assert_eq!(b.delay(0), Duration::from_secs(1));
+        assert_eq!(b.delay(2), Duration::from_secs(4));
+        assert_eq!(b.delay(3), Duration::from_secs(5));
+        assert_eq!(b.delay(100), Duration::from_secs(5));
+    }
+    #[test]
+    fn reconnect_after_close() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (l, a) = local_socket_pair().await.unwrap();
+            let server = tokio::spawn(async move {
+                // the first connection is closed right away
+                drop(l.accept().await.unwrap());
+                let (mut s, _) = l.accept().await.unwrap();
+                s.write_all(b"hello").await.unwrap();
+                let mut buf = [0u8; 2];
+                s.read_exact(&mut buf).await.unwrap();
+                buf
+            });
+            let backoff = Backoff::new().initial_delay(Duration::from_millis(10));
+            let mut c = ReconnectStream::connect(a, backoff).await.unwrap();
+            let mut buf = [0u8; 5];
+            c.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"hello");
+            c.write_all(b"ok").await.unwrap();
+            assert_eq!(&server.await.unwrap(), b"ok");
+        });
+    }
+    #[test]
+    fn give_up() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (l, a) = local_socket_pair().await.unwrap();
+            let backoff = Backoff::new()
+                .initial_delay(Duration::from_millis(1))
+                .attempts(2);
+            let connect = ReconnectStream::connect(a, backoff);
+            let (c, s) = tokio::join!(connect, l.accept());
+            let mut c = c.unwrap();
+            drop(s);
+            drop(l);
+            let mut buf = [0u8; 5];
+            let e = c.read(&mut buf).await.unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused);
+        });
+    }
+}
@@ -0,0 +1,120 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+
+use crate::{Addr, Stream};
+
+/// Looks up the addresses of a host name, for [`Stream::connect_with_resolver`].
+///
+/// This allows plugging in a resolver other than the one of the system,
+/// like one that talks DNS itself or a fixed table for tests.
+/// ```
+/// # use async_stream_connection::{Addr, Resolver};
+/// # use std::{future::Future, io, pin::Pin};
+/// struct Localhost;
+///
+/// impl Resolver for Localhost {
+///     fn resolve<'a>(
+///         &'a self,
+///         _host: &'a str,
+///         port: u16,
+///     ) -> Pin<Box<dyn Future<Output = io::Result<Vec<Addr>>> + Send + 'a>> {
+///         Box::pin(async move { Ok(vec![Addr::Inet(([127, 0, 0, 1], port).into())]) })
+///     }
+/// }
+/// ```
+pub trait Resolver: Send + Sync {
+    /// Returns the addresses of `host`, with `port` filled in, in the order they should be tried.
+    fn resolve<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> Pin<Box<dyn Future<Output = io::Result<Vec<Addr>>> + Send + 'a>>;
+}
+
+/// The resolver of the system, as used by [`Addr::resolve`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn resolve<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> Pin<Box<dyn Future<Output = io::Result<Vec<Addr>>> + Send + 'a>> {
+        Box::pin(async move {
+            Ok(tokio::net::lookup_host((host, port))
+                .await?
+                .map(Addr::Inet)
+                .collect())
+        })
+    }
+}
+
+impl Stream {
+    /// Opens a connection to `host`:`port`, looking up `host` with `resolver`.
+    ///
+    /// The addresses are tried one after the other and the first connection is returned.
+    /// If all of them fail, the last error is returned.
+    /// If there is no address at all, an error of kind [`io::ErrorKind::AddrNotAvailable`] is returned.
+    pub async fn connect_with_resolver(
+        host: &str,
+        port: u16,
+        resolver: &dyn Resolver,
+    ) -> io::Result<Stream> {
+        let mut last = io::Error::from(io::ErrorKind::AddrNotAvailable);
+        for a in resolver.resolve(host, port).await? {
+            match Stream::connect(&a).await {
+                Ok(s) => return Ok(s),
+                Err(e) => last = e,
+            }
+        }
+        Err(last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::tests::local_socket_pair;
+    use tokio::runtime::Builder;
+
+    /// Resolves everything to one address
+    struct Fixed(Addr);
+
+    impl Resolver for Fixed {
+        fn resolve<'a>(
+            &'a self,
+            host: &'a str,
+            _port: u16,
+        ) -> Pin<Box<dyn Future<Output = io::Result<Vec<Addr>>> + Send + 'a>> {
+            assert_eq!(host, "backend.internal");
+            Box::pin(async move { Ok(vec![self.0.clone()]) })
+        }
+    }
+
+    #[test]
+    fn stub() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (l, a) = local_socket_pair().await.unwrap();
+            let port = a.as_inet().unwrap().port();
+            let resolver = Fixed(a.clone());
+            let (c, s) = tokio::join!(
+                Stream::connect_with_resolver("backend.internal", port, &resolver),
+                l.accept()
+            );
+            assert_eq!(c.unwrap().peer_addr().unwrap(), a);
+            s.unwrap();
+        });
+    }
+    #[test]
+    fn system() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let all = SystemResolver.resolve("localhost", 80).await.unwrap();
+            assert!(!all.is_empty());
+            assert!(all.iter().all(|a| a.as_inet().unwrap().port() == 80));
+        });
+    }
+}
@@ -0,0 +1,179 @@
+use std::future::Future;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use crate::{Addr, Listener, Stream};
+
+/// Minimal pause before accepting again after running out of file descriptors
+const EXHAUSTED_DELAY: Duration = Duration::from_millis(10);
+
+/// [`EXHAUSTED_DELAY`] plus up to the same amount of jitter,
+/// so that several listeners don't retry in lockstep
+fn exhausted_delay() -> Duration {
+    use std::hash::{BuildHasher, Hasher};
+    let random = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    EXHAUSTED_DELAY + EXHAUSTED_DELAY.mul_f64((random % 1000) as f64 / 1000.0)
+}
+
+/// Calls `accept` until it does not fail for lack of file descriptors
+async fn retry_exhausted<F, Fut, T>(mut accept: F) -> io::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = io::Result<T>>,
+{
+    loop {
+        match accept().await {
+            Err(e) if is_exhausted(&e) => tokio::time::sleep(exhausted_delay()).await,
+            r => return r,
+        }
+    }
+}
+
+/// The process or system is out of file descriptors (`EMFILE` / `ENFILE`)
+fn is_exhausted(e: &io::Error) -> bool {
+    #[cfg(unix)]
+    let codes = [libc::EMFILE, libc::ENFILE];
+    #[cfg(windows)]
+    let codes = [10024]; // WSAEMFILE
+    e.raw_os_error().is_some_and(|c| codes.contains(&c))
+}
+
+/// The error concerns only a single connection
+fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::Interrupted
+    )
+}
+
+impl Listener {
+    /// Like [`Listener::accept`], but waits instead of failing
+    /// if the process or system is out of file descriptors (`EMFILE` / `ENFILE`).
+    ///
+    /// A plain accept loop would spin on this error, as the pending connection stays in the queue.
+    /// Here, accepting is retried after 10 to 20ms, giving other connections time to close.
+    pub async fn accept_with_backoff(&self) -> io::Result<(Stream, Addr)> {
+        retry_exhausted(|| self.accept()).await
+    }
+    /// Accepts connections and spawns `handler` for each of them,
+    /// with at most `max_conns` handlers running at the same time.
+    ///
+    /// Once the limit is reached, no further connections are accepted until a handler finishes.
+    /// Errors of a single connection (like [`io::ErrorKind::ConnectionAborted`]) are skipped.
+    /// If the process runs out of file descriptors, accepting pauses for a moment,
+    /// see [`Listener::accept_with_backoff`].
+    /// Any other error ends the loop and is returned.
+    /// ```no_run
+    /// # use async_stream_connection::Listener;
+    /// use tokio::io::AsyncWriteExt;
+    /// # async fn doc() -> std::io::Result<()> {
+    /// let l = Listener::bind(&"127.0.0.1:8080".parse()?).await?;
+    /// l.serve(100, |mut s, _peer| async move {
+    ///     let _ = s.write_all(b"hello\n").await;
+    /// })
+    /// .await
+    /// # }
+    /// ```
+    pub async fn serve<F, Fut>(self, max_conns: usize, handler: F) -> io::Result<()>
+    where
+        F: Fn(Stream, Addr) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let limit = Arc::new(Semaphore::new(max_conns));
+        loop {
+            let permit = limit
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+            match self.accept_with_backoff().await {
+                Ok((s, peer)) => {
+                    let fut = handler(s, peer);
+                    tokio::spawn(async move {
+                        fut.await;
+                        drop(permit);
+                    });
+                }
+                Err(e) if is_transient(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::AsyncReadExt;
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn exhausted() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            #[cfg(unix)]
+            let emfile = libc::EMFILE;
+            #[cfg(windows)]
+            let emfile = 10024;
+            let mut calls = 0;
+            let start = std::time::Instant::now();
+            let r = retry_exhausted(|| {
+                calls += 1;
+                let r = if calls < 3 {
+                    Err(io::Error::from_raw_os_error(emfile))
+                } else {
+                    Ok(calls)
+                };
+                async move { r }
+            })
+            .await;
+            assert_eq!(r.unwrap(), 3);
+            assert!(start.elapsed() >= 2 * EXHAUSTED_DELAY);
+
+            let r: io::Result<()> =
+                retry_exhausted(|| async { Err(io::ErrorKind::InvalidData.into()) }).await;
+            assert_eq!(r.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        });
+    }
+    #[test]
+    fn max_conns() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            let a = l.local_addr().unwrap();
+            let running = Arc::new(AtomicUsize::new(0));
+            let most = Arc::new(AtomicUsize::new(0));
+            let (r, m) = (running.clone(), most.clone());
+            tokio::spawn(l.serve(2, move |s, _| {
+                let (r, m) = (r.clone(), m.clone());
+                async move {
+                    let now = r.fetch_add(1, Ordering::SeqCst) + 1;
+                    m.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    r.fetch_sub(1, Ordering::SeqCst);
+                    drop(s);
+                }
+            }));
+            let mut clients = Vec::new();
+            for _ in 0..6 {
+                let a = a.clone();
+                clients.push(tokio::spawn(async move {
+                    let mut c = Stream::connect(&a).await.unwrap();
+                    let mut buf = Vec::new();
+                    c.read_to_end(&mut buf).await.unwrap();
+                }));
+            }
+            for c in clients {
+                c.await.unwrap();
+            }
+            assert!(most.load(Ordering::SeqCst) <= 2);
+        });
+    }
+}
@@ -0,0 +1,138 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncWrite, Error};
+
+use crate::Stream;
+
+type Writable = Pin<Box<dyn Future<Output = io::Result<()>> + Send>>;
+
+/// A cloneable handle to a [`Stream`], created by [`Stream::into_shared`].
+///
+/// All clones write to the same socket.
+/// Each `poll_write` is a single write to the socket,
+/// so a buffer written by one handle is never split by another handle,
+/// unless the socket accepts only part of it.
+/// This can happen with [`AsyncWriteExt::write_all`](tokio::io::AsyncWriteExt::write_all) on a full socket,
+/// where the remainder might end up after data of other handles.
+/// Send complete messages with a single write if ordering matters.
+///
+/// Reading needs exclusive access, see [`SharedStream::try_into_inner`].
+pub struct SharedStream {
+    inner: Arc<Stream>,
+    writable: Option<Writable>,
+}
+
+impl Stream {
+    /// Turns this stream into a handle that can be cloned to write from several tasks.
+    pub fn into_shared(self) -> SharedStream {
+        SharedStream {
+            inner: Arc::new(self),
+            writable: None,
+        }
+    }
+}
+
+impl SharedStream {
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &Stream {
+        &self.inner
+    }
+    /// Returns the underlying stream if this is the last handle to it.
+    pub fn try_into_inner(self) -> Result<Stream, SharedStream> {
+        Arc::try_unwrap(self.inner).map_err(|inner| SharedStream {
+            inner,
+            writable: None,
+        })
+    }
+}
+
+impl Clone for SharedStream {
+    fn clone(&self) -> Self {
+        SharedStream {
+            inner: self.inner.clone(),
+            writable: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for SharedStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SharedStream").field(&self.inner).finish()
+    }
+}
+
+impl AsyncWrite for SharedStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        let this = &mut *self;
+        loop {
+            match this.inner.try_write(buf) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                r => {
+                    this.writable = None;
+                    return Poll::Ready(r);
+                }
+            }
+            // every handle waits on its own, so that no wakeup is lost
+            let writable = this.writable.get_or_insert_with(|| {
+                let s = this.inner.clone();
+                Box::pin(async move { s.writable().await })
+            });
+            let r = ready!(writable.as_mut().poll(cx));
+            this.writable = None;
+            r?;
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Shuts down the write direction for all handles.
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Result<(), Error>> {
+        Poll::Ready(self.inner.shutdown_write().map_err(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stream::tests::tcp_pair;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn two_writers() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, mut s) = tcp_pair().await.unwrap();
+            let c = c.into_shared();
+            let mut tasks = Vec::new();
+            for b in [b'a', b'b'] {
+                let mut c = c.clone();
+                tasks.push(tokio::spawn(async move {
+                    for _ in 0..100 {
+                        c.write_all(&[b; 10]).await.unwrap();
+                    }
+                }));
+            }
+            for t in tasks {
+                t.await.unwrap();
+            }
+            let mut c = c.try_into_inner().unwrap();
+            c.shutdown().await.unwrap();
+            let mut buf = Vec::new();
+            s.read_to_end(&mut buf).await.unwrap();
+            assert_eq!(buf.len(), 2000);
+            assert_eq!(buf.iter().filter(|&&b| b == b'a').count(), 1000);
+            for chunk in buf.chunks(10) {
+                assert!(chunk.iter().all(|&b| b == chunk[0]));
+            }
+        });
+    }
+}
@@ -0,0 +1,194 @@
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use std::io;
+use std::net::IpAddr;
+
+use crate::{Addr, Stream};
+
+const VERSION: u8 = 5;
+const NO_AUTH: u8 = 0;
+const USER_PASS: u8 = 2;
+const NO_ACCEPTABLE: u8 = 0xff;
+const CONNECT: u8 = 1;
+const ATYP_V4: u8 = 1;
+const ATYP_DOMAIN: u8 = 3;
+const ATYP_V6: u8 = 4;
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Error for a reply code other than "succeeded"
+fn reply_error(rep: u8) -> io::Error {
+    let (kind, msg) = match rep {
+        1 => (io::ErrorKind::Other, "general SOCKS server failure"),
+        2 => (io::ErrorKind::PermissionDenied, "connection not allowed by ruleset"),
+        3 => (io::ErrorKind::NetworkUnreachable, "network unreachable"),
+        4 => (io::ErrorKind::HostUnreachable, "host unreachable"),
+        5 => (io::ErrorKind::ConnectionRefused, "connection refused"),
+        6 => (io::ErrorKind::TimedOut, "TTL expired"),
+        7 => (io::ErrorKind::Unsupported, "command not supported"),
+        8 => (io::ErrorKind::Unsupported, "address type not supported"),
+        _ => (io::ErrorKind::InvalidData, "unknown SOCKS reply"),
+    };
+    io::Error::new(kind, msg)
+}
+
+impl Stream {
+    /// Connects to `target_host`:`target_port` through the SOCKS5 proxy at `proxy`.
+    ///
+    /// `target_host` can be an IP address or a host name.
+    /// Host names are resolved by the proxy.
+    /// With `auth`, username/password authentication (RFC 1929) is offered in addition to none.
+    ///
+    /// Failure replies of the proxy are mapped to the closest [`io::ErrorKind`],
+    /// like [`io::ErrorKind::ConnectionRefused`].
+    pub async fn connect_via_socks5(
+        proxy: &Addr,
+        target_host: &str,
+        target_port: u16,
+        auth: Option<(String, String)>,
+    ) -> io::Result<Stream> {
+        let mut s = Stream::connect(proxy).await?;
+
+        // method selection
+        let greeting: &[u8] = match auth {
+            Some(_) => &[VERSION, 2, NO_AUTH, USER_PASS],
+            None => &[VERSION, 1, NO_AUTH],
+        };
+        s.write_all(greeting).await?;
+        let mut reply = [0u8; 2];
+        s.read_exact(&mut reply).await?;
+        if reply[0] != VERSION {
+            return Err(invalid("not a SOCKS5 proxy"));
+        }
+        match (reply[1], auth) {
+            (NO_AUTH, _) => {}
+            (USER_PASS, Some((user, pass))) => {
+                let (user, pass) = (user.as_bytes(), pass.as_bytes());
+                let user_len = u8::try_from(user.len())
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "username too long"))?;
+                let pass_len = u8::try_from(pass.len())
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "password too long"))?;
+                let mut req = Vec::with_capacity(3 + user.len() + pass.len());
+                req.extend_from_slice(&[1, user_len]);
+                req.extend_from_slice(user);
+                req.push(pass_len);
+                req.extend_from_slice(pass);
+                s.write_all(&req).await?;
+                s.read_exact(&mut reply).await?;
+                if reply[1] != 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        "SOCKS authentication failed",
+                    ));
+                }
+            }
+            (NO_ACCEPTABLE, _) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "no acceptable SOCKS authentication method",
+                ))
+            }
+            _ => return Err(invalid("unexpected SOCKS authentication method")),
+        }
+
+        // connect request
+        let mut req = vec![VERSION, CONNECT, 0];
+        match target_host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => {
+                req.push(ATYP_V4);
+                req.extend_from_slice(&ip.octets());
+            }
+            Ok(IpAddr::V6(ip)) => {
+                req.push(ATYP_V6);
+                req.extend_from_slice(&ip.octets());
+            }
+            Err(_) => {
+                let len = u8::try_from(target_host.len())
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "host name too long"))?;
+                req.extend_from_slice(&[ATYP_DOMAIN, len]);
+                req.extend_from_slice(target_host.as_bytes());
+            }
+        }
+        req.extend_from_slice(&target_port.to_be_bytes());
+        s.write_all(&req).await?;
+
+        let mut reply = [0u8; 4];
+        s.read_exact(&mut reply).await?;
+        if reply[0] != VERSION {
+            return Err(invalid("not a SOCKS5 proxy"));
+        }
+        if reply[1] != 0 {
+            return Err(reply_error(reply[1]));
+        }
+        // skip the bound address and port
+        let len = match reply[3] {
+            ATYP_V4 => 4,
+            ATYP_V6 => 16,
+            ATYP_DOMAIN => s.read_u8().await? as usize,
+            _ => return Err(invalid("unknown SOCKS address type")),
+        };
+        let mut bound = vec![0u8; len + 2];
+        s.read_exact(&mut bound).await?;
+        Ok(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::tests::local_socket_pair;
+    use tokio::net::TcpStream;
+    use tokio::runtime::Builder;
+
+    /// Accepts `user`/`pass` and expects a connect to `example.com:80`
+    async fn stub(mut s: TcpStream, rep: u8) {
+        let mut buf = [0u8; 4];
+        s.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, [5, 2, 0, 2]);
+        s.write_all(&[5, 2]).await.unwrap();
+        let mut buf = [0u8; 11];
+        s.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"\x01\x04user\x04pass");
+        s.write_all(&[1, 0]).await.unwrap();
+        let mut buf = [0u8; 18];
+        s.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"\x05\x01\x00\x03\x0bexample.com\x00\x50");
+        s.write_all(&[5, rep, 0, 1, 127, 0, 0, 1, 0, 80]).await.unwrap();
+        if rep == 0 {
+            s.write_all(b"tunneled").await.unwrap();
+        }
+    }
+
+    #[test]
+    fn connect() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (l, a) = local_socket_pair().await.unwrap();
+            let server = tokio::spawn(async move { stub(l.accept().await.unwrap().0, 0).await });
+            let auth = Some(("user".to_string(), "pass".to_string()));
+            let mut s = Stream::connect_via_socks5(&a, "example.com", 80, auth)
+                .await
+                .unwrap();
+            let mut buf = [0u8; 8];
+            s.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"tunneled");
+            server.await.unwrap();
+        });
+    }
+    #[test]
+    fn refused() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (l, a) = local_socket_pair().await.unwrap();
+            let server = tokio::spawn(async move { stub(l.accept().await.unwrap().0, 5).await });
+            let auth = Some(("user".to_string(), "pass".to_string()));
+            let e = Stream::connect_via_socks5(&a, "example.com", 80, auth)
+                .await
+                .unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused);
+            server.await.unwrap();
+        });
+    }
+}
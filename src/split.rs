@@ -0,0 +1,361 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, Error, ReadBuf};
+use tokio::net::tcp;
+#[cfg(all(unix, feature = "unix"))]
+use tokio::net::unix;
+
+use crate::Stream;
+
+/// Borrowed read half of a [`Stream`], created by [`Stream::split`].
+#[derive(Debug)]
+pub enum ReadHalf<'a> {
+    /// Read half of a TCP stream
+    Inet(tcp::ReadHalf<'a>),
+    #[cfg(all(unix, feature = "unix"))]
+    /// Read half of a Unix stream
+    Unix(unix::ReadHalf<'a>),
+    #[cfg(windows)]
+    /// Read half of a named pipe
+    Pipe(tokio::io::ReadHalf<&'a mut Stream>),
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    /// Read half of a vsock stream
+    Vsock(tokio::io::ReadHalf<&'a mut Stream>),
+    #[cfg(feature = "test-util")]
+    /// Read half of a mock stream
+    Mock(tokio::io::ReadHalf<&'a mut Stream>),
+}
+
+/// Borrowed write half of a [`Stream`], created by [`Stream::split`].
+#[derive(Debug)]
+pub enum WriteHalf<'a> {
+    /// Write half of a TCP stream
+    Inet(tcp::WriteHalf<'a>),
+    #[cfg(all(unix, feature = "unix"))]
+    /// Write half of a Unix stream
+    Unix(unix::WriteHalf<'a>),
+    #[cfg(windows)]
+    /// Write half of a named pipe
+    Pipe(tokio::io::WriteHalf<&'a mut Stream>),
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    /// Write half of a vsock stream
+    Vsock(tokio::io::WriteHalf<&'a mut Stream>),
+    #[cfg(feature = "test-util")]
+    /// Write half of a mock stream
+    Mock(tokio::io::WriteHalf<&'a mut Stream>),
+}
+
+/// Owned read half of a [`Stream`], created by [`Stream::into_split`].
+#[derive(Debug)]
+pub enum OwnedReadHalf {
+    /// Read half of a TCP stream
+    Inet(tcp::OwnedReadHalf),
+    #[cfg(all(unix, feature = "unix"))]
+    /// Read half of a Unix stream
+    Unix(unix::OwnedReadHalf),
+    #[cfg(windows)]
+    /// Read half of a named pipe
+    Pipe(tokio::io::ReadHalf<Stream>),
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    /// Read half of a vsock stream
+    Vsock(tokio::io::ReadHalf<Stream>),
+    #[cfg(feature = "test-util")]
+    /// Read half of a mock stream
+    Mock(tokio::io::ReadHalf<Stream>),
+}
+
+/// Owned write half of a [`Stream`], created by [`Stream::into_split`].
+///
+/// Dropping it (or calling [`AsyncWriteExt::shutdown`](tokio::io::AsyncWriteExt::shutdown))
+/// shuts down the write direction of the connection.
+#[derive(Debug)]
+pub enum OwnedWriteHalf {
+    /// Write half of a TCP stream
+    Inet(tcp::OwnedWriteHalf),
+    #[cfg(all(unix, feature = "unix"))]
+    /// Write half of a Unix stream
+    Unix(unix::OwnedWriteHalf),
+    #[cfg(windows)]
+    /// Write half of a named pipe
+    Pipe(tokio::io::WriteHalf<Stream>),
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    /// Write half of a vsock stream
+    Vsock(tokio::io::WriteHalf<Stream>),
+    #[cfg(feature = "test-util")]
+    /// Write half of a mock stream
+    Mock(tokio::io::WriteHalf<Stream>),
+}
+
+impl Stream {
+    /// Splits a `Stream` into a read half and a write half,
+    /// which can be used to read and write the stream concurrently.
+    ///
+    /// The halves borrow the stream. See [`Stream::into_split`] for owned halves.
+    pub fn split(&mut self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+        match self {
+            Stream::Inet(s) => {
+                let (r, w) = s.split();
+                (ReadHalf::Inet(r), WriteHalf::Inet(w))
+            }
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => {
+                let (r, w) = s.split();
+                (ReadHalf::Unix(r), WriteHalf::Unix(w))
+            }
+            #[cfg(windows)]
+            pipe @ (Stream::Pipe(_) | Stream::PipeServer(_)) => {
+                let (r, w) = tokio::io::split(pipe);
+                (ReadHalf::Pipe(r), WriteHalf::Pipe(w))
+            }
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            vsock @ Stream::Vsock(_) => {
+                let (r, w) = tokio::io::split(vsock);
+                (ReadHalf::Vsock(r), WriteHalf::Vsock(w))
+            }
+            #[cfg(feature = "test-util")]
+            mock @ Stream::Mock(_) => {
+                let (r, w) = tokio::io::split(mock);
+                (ReadHalf::Mock(r), WriteHalf::Mock(w))
+            }
+        }
+    }
+
+    /// Splits a `Stream` into a read half and a write half,
+    /// which can be moved into different tasks.
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        match self {
+            Stream::Inet(s) => {
+                let (r, w) = s.into_split();
+                (OwnedReadHalf::Inet(r), OwnedWriteHalf::Inet(w))
+            }
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => {
+                let (r, w) = s.into_split();
+                (OwnedReadHalf::Unix(r), OwnedWriteHalf::Unix(w))
+            }
+            #[cfg(windows)]
+            pipe @ (Stream::Pipe(_) | Stream::PipeServer(_)) => {
+                let (r, w) = tokio::io::split(pipe);
+                (OwnedReadHalf::Pipe(r), OwnedWriteHalf::Pipe(w))
+            }
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            vsock @ Stream::Vsock(_) => {
+                let (r, w) = tokio::io::split(vsock);
+                (OwnedReadHalf::Vsock(r), OwnedWriteHalf::Vsock(w))
+            }
+            #[cfg(feature = "test-util")]
+            mock @ Stream::Mock(_) => {
+                let (r, w) = tokio::io::split(mock);
+                (OwnedReadHalf::Mock(r), OwnedWriteHalf::Mock(w))
+            }
+        }
+    }
+}
+
+impl AsyncRead for ReadHalf<'_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        match &mut *self {
+            ReadHalf::Inet(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+            #[cfg(all(unix, feature = "unix"))]
+            ReadHalf::Unix(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+            #[cfg(windows)]
+            ReadHalf::Pipe(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            ReadHalf::Vsock(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+            #[cfg(feature = "test-util")]
+            ReadHalf::Mock(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+        }
+    }
+}
+impl AsyncRead for OwnedReadHalf {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        match &mut *self {
+            OwnedReadHalf::Inet(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+            #[cfg(all(unix, feature = "unix"))]
+            OwnedReadHalf::Unix(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+            #[cfg(windows)]
+            OwnedReadHalf::Pipe(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            OwnedReadHalf::Vsock(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+            #[cfg(feature = "test-util")]
+            OwnedReadHalf::Mock(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+        }
+    }
+}
+impl AsyncWrite for WriteHalf<'_> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        match &mut *self {
+            WriteHalf::Inet(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+            #[cfg(all(unix, feature = "unix"))]
+            WriteHalf::Unix(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+            #[cfg(windows)]
+            WriteHalf::Pipe(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            WriteHalf::Vsock(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+            #[cfg(feature = "test-util")]
+            WriteHalf::Mock(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        match &mut *self {
+            WriteHalf::Inet(s) => Pin::new(s).as_mut().poll_flush(cx),
+            #[cfg(all(unix, feature = "unix"))]
+            WriteHalf::Unix(s) => Pin::new(s).as_mut().poll_flush(cx),
+            #[cfg(windows)]
+            WriteHalf::Pipe(s) => Pin::new(s).as_mut().poll_flush(cx),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            WriteHalf::Vsock(s) => Pin::new(s).as_mut().poll_flush(cx),
+            #[cfg(feature = "test-util")]
+            WriteHalf::Mock(s) => Pin::new(s).as_mut().poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        match &mut *self {
+            WriteHalf::Inet(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+            #[cfg(all(unix, feature = "unix"))]
+            WriteHalf::Unix(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+            #[cfg(windows)]
+            WriteHalf::Pipe(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            WriteHalf::Vsock(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+            #[cfg(feature = "test-util")]
+            WriteHalf::Mock(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+        }
+    }
+}
+impl AsyncWrite for OwnedWriteHalf {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        match &mut *self {
+            OwnedWriteHalf::Inet(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+            #[cfg(all(unix, feature = "unix"))]
+            OwnedWriteHalf::Unix(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+            #[cfg(windows)]
+            OwnedWriteHalf::Pipe(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            OwnedWriteHalf::Vsock(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+            #[cfg(feature = "test-util")]
+            OwnedWriteHalf::Mock(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        match &mut *self {
+            OwnedWriteHalf::Inet(s) => Pin::new(s).as_mut().poll_flush(cx),
+            #[cfg(all(unix, feature = "unix"))]
+            OwnedWriteHalf::Unix(s) => Pin::new(s).as_mut().poll_flush(cx),
+            #[cfg(windows)]
+            OwnedWriteHalf::Pipe(s) => Pin::new(s).as_mut().poll_flush(cx),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            OwnedWriteHalf::Vsock(s) => Pin::new(s).as_mut().poll_flush(cx),
+            #[cfg(feature = "test-util")]
+            OwnedWriteHalf::Mock(s) => Pin::new(s).as_mut().poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        match &mut *self {
+            OwnedWriteHalf::Inet(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+            #[cfg(all(unix, feature = "unix"))]
+            OwnedWriteHalf::Unix(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+            #[cfg(windows)]
+            OwnedWriteHalf::Pipe(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            OwnedWriteHalf::Vsock(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+            #[cfg(feature = "test-util")]
+            OwnedWriteHalf::Mock(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::tests::tcp_pair;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::runtime::Builder;
+
+    /// echo everything until EOF, then close
+    async fn echo(s: Stream) {
+        let (mut r, mut w) = s.into_split();
+        tokio::io::copy(&mut r, &mut w).await.unwrap();
+        w.shutdown().await.unwrap();
+    }
+
+    async fn roundtrip(c: Stream) {
+        let (mut r, mut w) = c.into_split();
+        let reader = tokio::spawn(async move {
+            let mut buf = Vec::new();
+            r.read_to_end(&mut buf).await.unwrap();
+            buf
+        });
+        w.write_all(b"1234").await.unwrap();
+        w.shutdown().await.unwrap();
+        assert_eq!(reader.await.unwrap(), b"1234");
+    }
+
+    #[test]
+    fn tcp_into_split() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = tcp_pair().await.unwrap();
+            tokio::spawn(echo(s));
+            roundtrip(c).await;
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_into_split() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = tokio::net::UnixStream::pair().unwrap();
+            tokio::spawn(echo(s.into()));
+            roundtrip(c.into()).await;
+        });
+    }
+    #[test]
+    fn tcp_split() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, s) = tcp_pair().await.unwrap();
+            tokio::spawn(echo(s));
+            let (mut r, mut w) = c.split();
+            w.write_all(b"1234").await.unwrap();
+            let mut buf = [0u8; 4];
+            r.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"1234");
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_split() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = tokio::net::UnixStream::pair().unwrap();
+            tokio::spawn(echo(s.into()));
+            let mut c = Stream::from(c);
+            let (mut r, mut w) = c.split();
+            w.write_all(b"1234").await.unwrap();
+            let mut buf = [0u8; 4];
+            r.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"1234");
+        });
+    }
+}
@@ -1,22 +1,59 @@
+use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite, Error, ReadBuf};
-use tokio::net::TcpStream;
-#[cfg(unix)]
-use tokio::net::UnixStream;
+use tokio::net::{TcpSocket, TcpStream};
+use tokio::io::{Interest, Ready};
+#[cfg(all(unix, feature = "unix"))]
+use tokio::net::{unix::UCred, UnixStream};
+#[cfg(windows)]
+use tokio::net::windows::named_pipe::{ClientOptions, NamedPipeClient, NamedPipeServer};
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+use tokio_vsock::{VsockAddr, VsockStream};
 
 use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(all(unix, not(feature = "test-util")))]
+use std::os::unix::io::{AsFd, BorrowedFd};
+
+use socket2::{Domain, SockRef, Socket, TcpKeepalive, Type};
+#[cfg(all(unix, feature = "unix"))]
+use socket2::SockAddr;
 
 use crate::Addr;
+#[cfg(any(unix, windows))]
+use crate::unsupported;
 
 /// A socket connected to an endpoint
 #[derive(Debug)]
 pub enum Stream {
     /// A TCP stream between a local and a remote socket.
     Inet(TcpStream),
-    #[cfg(unix)]
+    #[cfg(all(unix, feature = "unix"))]
     /// A connected Unix socket
     Unix(UnixStream),
+    #[cfg(windows)]
+    /// Client end of a Windows named pipe
+    Pipe(NamedPipeClient),
+    #[cfg(windows)]
+    /// Server end of a Windows named pipe, as returned by [`Listener::accept`](crate::Listener::accept)
+    PipeServer(NamedPipeServer),
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "vsock", target_os = "linux"))))]
+    /// A vsock stream between a VM and its host
+    Vsock(VsockStream),
+    #[cfg(feature = "test-util")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+    /// An in-memory stream for tests, created by [`Stream::mock`]
+    ///
+    /// It has no addresses, socket options or readiness, these functions return
+    /// [`io::ErrorKind::Unsupported`].
+    /// It also has no file descriptor, so `Stream` does not implement `AsRawFd` and `AsFd`
+    /// while this feature is enabled. Use [`Stream::raw_fd`] instead.
+    Mock(tokio::io::DuplexStream),
 }
 
 impl From<TcpStream> for Stream {
@@ -25,38 +62,1026 @@ impl From<TcpStream> for Stream {
     }
 }
 
-#[cfg(unix)]
+#[cfg(all(unix, feature = "unix"))]
 impl From<UnixStream> for Stream {
     fn from(s: UnixStream) -> Stream {
         Stream::Unix(s)
     }
 }
 
+#[cfg(windows)]
+impl From<NamedPipeClient> for Stream {
+    fn from(s: NamedPipeClient) -> Stream {
+        Stream::Pipe(s)
+    }
+}
+
+#[cfg(windows)]
+impl From<NamedPipeServer> for Stream {
+    fn from(s: NamedPipeServer) -> Stream {
+        Stream::PipeServer(s)
+    }
+}
+
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+impl From<VsockStream> for Stream {
+    fn from(s: VsockStream) -> Stream {
+        Stream::Vsock(s)
+    }
+}
+
+/// Opens the client end of a named pipe, waiting while all instances are busy
+#[cfg(windows)]
+async fn connect_pipe(name: &str) -> io::Result<NamedPipeClient> {
+    /// `ERROR_PIPE_BUSY`
+    const PIPE_BUSY: i32 = 231;
+    loop {
+        match ClientOptions::new().open(name) {
+            Err(e) if e.raw_os_error() == Some(PIPE_BUSY) => {}
+            r => return r,
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
 impl Stream {
     /// Opens a connection to a remote host.
-    pub async fn connect(s: &Addr) -> io::Result<Stream> {
-        match s {
+    pub async fn connect(addr: &Addr) -> io::Result<Stream> {
+        let r = match addr {
             Addr::Inet(s) => TcpStream::connect(s).await.map(Stream::Inet),
-            #[cfg(unix)]
+            #[cfg(all(unix, feature = "unix"))]
             Addr::Unix(s) => UnixStream::connect(s).await.map(Stream::Unix),
+            #[cfg(windows)]
+            Addr::Pipe(s) => connect_pipe(s).await.map(Stream::Pipe),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Addr::Vsock { cid, port } => VsockStream::connect(VsockAddr::new(*cid, *port))
+                .await
+                .map(Stream::Vsock),
+        };
+        crate::trace::connected(addr, &r);
+        r
+    }
+
+    /// Creates a new Stream from an inherited, connected socket, e.g. handed over by a sandbox.
+    ///
+    /// The socket family is inspected to decide whether a TCP or Unix stream is created.
+    /// The socket is put into nonblocking mode.
+    /// If the fd is not a stream socket of a supported family, an error of kind
+    /// [`io::ErrorKind::InvalidInput`] is returned and the fd is left open.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open socket that is not owned by anything else.
+    /// On success, the returned Stream takes ownership of it.
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub unsafe fn from_raw_fd_checked(fd: RawFd) -> io::Result<Stream> {
+        use std::os::unix::io::FromRawFd;
+
+        // don't close the fd if we reject it
+        let socket = std::mem::ManuallyDrop::new(Socket::from_raw_fd(fd));
+        if socket.r#type()? != Type::STREAM {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not a stream socket",
+            ));
+        }
+        let addr = socket.local_addr()?;
+        if addr.is_ipv4() || addr.is_ipv6() {
+            socket.set_nonblocking(true)?;
+            return TcpStream::from_std(std::mem::ManuallyDrop::into_inner(socket).into())
+                .map(Stream::Inet);
+        }
+        #[cfg(feature = "unix")]
+        if addr.is_unix() {
+            socket.set_nonblocking(true)?;
+            return UnixStream::from_std(std::mem::ManuallyDrop::into_inner(socket).into())
+                .map(Stream::Unix);
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "unsupported socket family",
+        ))
+    }
+
+    /// Creates a new Stream from a connected [`std::net::TcpStream`].
+    ///
+    /// The stream is put into nonblocking mode.
+    pub fn from_std_tcp(s: std::net::TcpStream) -> io::Result<Stream> {
+        s.set_nonblocking(true)?;
+        TcpStream::from_std(s).map(Stream::Inet)
+    }
+    /// Creates a new Stream from a connected [`std::os::unix::net::UnixStream`].
+    ///
+    /// The stream is put into nonblocking mode.
+    #[cfg(all(unix, feature = "unix"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+    pub fn from_std_unix(s: std::os::unix::net::UnixStream) -> io::Result<Stream> {
+        s.set_nonblocking(true)?;
+        UnixStream::from_std(s).map(Stream::Unix)
+    }
+    /// Turns this Stream into its std counterpart.
+    ///
+    /// The returned stream will be in nonblocking mode.
+    /// Named pipes, vsock and mock streams have no std counterpart and return an error of kind
+    /// [`io::ErrorKind::Unsupported`].
+    pub fn into_std(self) -> io::Result<StdStream> {
+        match self {
+            Stream::Inet(s) => s.into_std().map(StdStream::Inet),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => s.into_std().map(StdStream::Unix),
+            #[cfg(any(windows, all(feature = "vsock", target_os = "linux"), feature = "test-util"))]
+            _ => Err(unsupported("into_std", self.variant()).into()),
+        }
+    }
+
+    /// Creates two connected streams, for tests that should not bind any ports.
+    ///
+    /// On Unix, this is a [`UnixStream::pair`].
+    /// Elsewhere, it is a TCP connection over the loopback interface.
+    /// Must be called from within a tokio runtime.
+    pub fn pair() -> io::Result<(Stream, Stream)> {
+        #[cfg(all(unix, feature = "unix"))]
+        {
+            let (a, b) = UnixStream::pair()?;
+            Ok((Stream::Unix(a), Stream::Unix(b)))
+        }
+        #[cfg(not(all(unix, feature = "unix")))]
+        {
+            let l = std::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, 0))?;
+            let a = std::net::TcpStream::connect(l.local_addr()?)?;
+            let (b, _) = l.accept()?;
+            a.set_nonblocking(true)?;
+            b.set_nonblocking(true)?;
+            Ok((
+                Stream::Inet(TcpStream::from_std(a)?),
+                Stream::Inet(TcpStream::from_std(b)?),
+            ))
+        }
+    }
+
+    /// Creates two connected in-memory streams, for tests without any OS sockets.
+    ///
+    /// Each direction buffers up to `capacity` bytes, see [`tokio::io::duplex`].
+    #[cfg(feature = "test-util")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+    pub fn mock(capacity: usize) -> (Stream, Stream) {
+        let (a, b) = tokio::io::duplex(capacity);
+        (Stream::Mock(a), Stream::Mock(b))
+    }
+
+    /// Like [`Stream::connect`], but takes anything that converts into an [`Addr`],
+    /// like a [`SocketAddr`](std::net::SocketAddr) or a [`Path`](std::path::Path).
+    pub async fn connect_to<A: Into<Addr>>(a: A) -> io::Result<Stream> {
+        Stream::connect(&a.into()).await
+    }
+
+    /// Opens a connection to `listener`, which is in the same process.
+    ///
+    /// This connects to [`Listener::local_addr`](crate::Listener::local_addr).
+    /// A TCP listener bound to an unspecified address like `0.0.0.0` is reached via loopback.
+    pub async fn connect_to_listener(listener: &crate::Listener) -> io::Result<Stream> {
+        match listener.local_addr()? {
+            Addr::Inet(mut a) if a.ip().is_unspecified() => {
+                a.set_ip(match a {
+                    SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+                    SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+                });
+                Stream::connect(&Addr::Inet(a)).await
+            }
+            a => Stream::connect(&a).await,
+        }
+    }
+
+    /// Name of the variant, for error messages
+    pub(crate) fn variant(&self) -> &'static str {
+        match self {
+            Stream::Inet(_) => "Stream::Inet",
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(_) => "Stream::Unix",
+            #[cfg(windows)]
+            Stream::Pipe(_) => "Stream::Pipe",
+            #[cfg(windows)]
+            Stream::PipeServer(_) => "Stream::PipeServer",
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => "Stream::Vsock",
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => "Stream::Mock",
+        }
+    }
+
+    /// Opens a connection to a remote host, binding the local side to `local` first.
+    ///
+    /// This selects the source address on multi-homed hosts.
+    /// Both addresses must be of the same family, otherwise an error of kind
+    /// [`io::ErrorKind::InvalidInput`] is returned.
+    /// Named pipes and vsock sockets can't be bound and return an error of kind
+    /// [`io::ErrorKind::Unsupported`].
+    pub async fn connect_from(local: &Addr, remote: &Addr) -> io::Result<Stream> {
+        match (local, remote) {
+            (Addr::Inet(local), Addr::Inet(remote)) if local.is_ipv4() == remote.is_ipv4() => {
+                let socket = Socket::new(Domain::for_address(*local), Type::STREAM, None)?;
+                socket.bind(&(*local).into())?;
+                socket.set_nonblocking(true)?;
+                let socket = TcpSocket::from_std_stream(socket.into());
+                socket.connect(*remote).await.map(Stream::Inet)
+            }
+            #[cfg(all(unix, feature = "unix"))]
+            (Addr::Unix(local), Addr::Unix(remote)) => {
+                let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+                socket.bind(&SockAddr::unix(local)?)?;
+                socket.set_nonblocking(true)?;
+                // connecting a Unix socket does not block
+                socket.connect(&SockAddr::unix(remote)?)?;
+                UnixStream::from_std(socket.into()).map(Stream::Unix)
+            }
+            #[cfg(windows)]
+            (Addr::Pipe(_), Addr::Pipe(_)) => Err(unsupported("connect_from", "Stream::Pipe").into()),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            (Addr::Vsock { .. }, Addr::Vsock { .. }) => {
+                Err(unsupported("connect_from", "Stream::Vsock").into())
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "local and remote address family differ",
+            )),
+        }
+    }
+
+    /// Opens a TCP connection that is bound to the network interface `ifname` (like `eth0`),
+    /// using `SO_BINDTODEVICE`.
+    ///
+    /// This needs `CAP_NET_RAW` and is only available on Linux.
+    /// Elsewhere, and for all other addresses, an error of kind [`io::ErrorKind::Unsupported`] is returned.
+    pub async fn connect_device(addr: &Addr, ifname: &str) -> io::Result<Stream> {
+        match addr {
+            #[cfg(target_os = "linux")]
+            Addr::Inet(remote) => {
+                let socket = Socket::new(Domain::for_address(*remote), Type::STREAM, None)?;
+                socket.bind_device(Some(ifname.as_bytes()))?;
+                socket.set_nonblocking(true)?;
+                let socket = TcpSocket::from_std_stream(socket.into());
+                socket.connect(*remote).await.map(Stream::Inet)
+            }
+            // there might be no other addresses
+            #[allow(unreachable_patterns)]
+            _ => {
+                let _ = ifname;
+                let variant = match addr {
+                    Addr::Inet(_) => "Stream::Inet",
+                    #[cfg(all(unix, feature = "unix"))]
+                    Addr::Unix(_) => "Stream::Unix",
+                    #[cfg(windows)]
+                    Addr::Pipe(_) => "Stream::Pipe",
+                    #[cfg(all(feature = "vsock", target_os = "linux"))]
+                    Addr::Vsock { .. } => "Stream::Vsock",
+                };
+                Err(unsupported("SO_BINDTODEVICE", variant).into())
+            }
+        }
+    }
+
+    /// Opens a connection to a remote host, giving up after `dur`.
+    ///
+    /// If the connection is not established in time, an error of kind
+    /// [`io::ErrorKind::TimedOut`] is returned.
+    pub async fn connect_timeout(s: &Addr, dur: Duration) -> io::Result<Stream> {
+        match tokio::time::timeout(dur, Stream::connect(s)).await {
+            Ok(r) => r,
+            Err(_) => Err(io::ErrorKind::TimedOut.into()),
         }
     }
 
+    /// Opens a TCP connection to `host`, racing all of its addresses (RFC 8305 "Happy Eyeballs").
+    ///
+    /// IPv6 and IPv4 addresses are tried alternately, starting with IPv6.
+    /// A new attempt is started every 250ms or as soon as the previous one failed.
+    /// The first established connection is returned and all other attempts are cancelled.
+    /// If all attempts fail, the last error is returned.
+    pub async fn connect_happy_eyeballs(host: &str, port: u16) -> io::Result<Stream> {
+        const ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+        let (v6, v4): (Vec<_>, Vec<_>) =
+            tokio::net::lookup_host((host, port)).await?.partition(|a| a.is_ipv6());
+        let mut v6 = v6.into_iter();
+        let mut v4 = v4.into_iter();
+        let mut addrs = std::iter::from_fn(move || {
+            let mut n = v6.next();
+            if n.is_some() {
+                std::mem::swap(&mut v6, &mut v4);
+            } else {
+                n = v4.next();
+            }
+            n
+        });
+
+        type Attempt = Pin<Box<dyn Future<Output = io::Result<TcpStream>> + Send>>;
+        let mut attempts: Vec<Attempt> = Vec::new();
+        let mut delay = Box::pin(tokio::time::sleep(ATTEMPT_DELAY));
+        let mut last_err = None;
+        std::future::poll_fn(|cx| loop {
+            let mut failed = false;
+            let mut i = 0;
+            while i < attempts.len() {
+                match attempts[i].as_mut().poll(cx) {
+                    Poll::Ready(Ok(s)) => return Poll::Ready(Ok(Stream::Inet(s))),
+                    Poll::Ready(Err(e)) => {
+                        drop(attempts.swap_remove(i));
+                        last_err = Some(e);
+                        failed = true;
+                    }
+                    Poll::Pending => i += 1,
+                }
+            }
+            if failed || attempts.is_empty() || delay.as_mut().poll(cx).is_ready() {
+                if let Some(a) = addrs.next() {
+                    attempts.push(Box::pin(TcpStream::connect(a)));
+                    delay.as_mut().reset(tokio::time::Instant::now() + ATTEMPT_DELAY);
+                    continue;
+                }
+                if attempts.is_empty() {
+                    return Poll::Ready(Err(last_err.take().unwrap_or_else(|| {
+                        io::Error::new(io::ErrorKind::AddrNotAvailable, "host did not resolve")
+                    })));
+                }
+            }
+            return Poll::Pending;
+        })
+        .await
+    }
+
     /// Returns the local address that this stream is bound to.
+    ///
+    /// Named pipes don't know their name and return an error of kind
+    /// [`io::ErrorKind::Unsupported`].
     pub fn local_addr(&self) -> io::Result<Addr> {
         match self {
             Stream::Inet(s) => s.local_addr().map(Addr::Inet),
-            #[cfg(unix)]
+            #[cfg(all(unix, feature = "unix"))]
             Stream::Unix(s) => s.local_addr().map(|e| e.into()),
+            #[cfg(windows)]
+            Stream::Pipe(_) | Stream::PipeServer(_) => Err(unsupported("local_addr", self.variant()).into()),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(s) => s.local_addr().map(Addr::from),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("local_addr", self.variant()).into()),
         }
     }
 
     /// Returns the remote address that this stream is connected to.
+    ///
+    /// Named pipes don't know their name and return an error of kind
+    /// [`io::ErrorKind::Unsupported`].
     pub fn peer_addr(&self) -> io::Result<Addr> {
         match self {
             Stream::Inet(s) => s.peer_addr().map(Addr::Inet),
-            #[cfg(unix)]
+            #[cfg(all(unix, feature = "unix"))]
             Stream::Unix(s) => s.peer_addr().map(|e| e.into()),
+            #[cfg(windows)]
+            Stream::Pipe(_) | Stream::PipeServer(_) => Err(unsupported("peer_addr", self.variant()).into()),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(s) => s.peer_addr().map(Addr::from),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("peer_addr", self.variant()).into()),
+        }
+    }
+
+    /// Returns `true` if the peer is on the same machine, see [`Addr::is_loopback`].
+    ///
+    /// This is always the case for Unix sockets, named pipes and mock streams.
+    /// For TCP, the peer IP decides. If it can't be determined, this is `false`.
+    pub fn is_loopback(&self) -> bool {
+        match self {
+            Stream::Inet(s) => s.peer_addr().is_ok_and(|a| a.ip().to_canonical().is_loopback()),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(_) => true,
+            #[cfg(windows)]
+            Stream::Pipe(_) | Stream::PipeServer(_) => true,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => false,
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => true,
+        }
+    }
+
+    /// Waits for any of the requested ready states.
+    ///
+    /// vsock streams return an error of kind [`io::ErrorKind::Unsupported`],
+    /// as do the other readiness and `try_` functions.
+    pub async fn ready(&self, interest: Interest) -> io::Result<Ready> {
+        match self {
+            Stream::Inet(s) => s.ready(interest).await,
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => s.ready(interest).await,
+            #[cfg(windows)]
+            Stream::Pipe(s) => s.ready(interest).await,
+            #[cfg(windows)]
+            Stream::PipeServer(s) => s.ready(interest).await,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("ready", self.variant()).into()),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("ready", self.variant()).into()),
+        }
+    }
+
+    /// Waits for the socket to become readable.
+    ///
+    /// Usually paired with [`Stream::try_read`].
+    pub async fn readable(&self) -> io::Result<()> {
+        match self {
+            Stream::Inet(s) => s.readable().await,
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => s.readable().await,
+            #[cfg(windows)]
+            Stream::Pipe(s) => s.readable().await,
+            #[cfg(windows)]
+            Stream::PipeServer(s) => s.readable().await,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("readable", self.variant()).into()),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("readable", self.variant()).into()),
+        }
+    }
+
+    /// Waits for the socket to become writable.
+    ///
+    /// Usually paired with [`Stream::try_write`].
+    pub async fn writable(&self) -> io::Result<()> {
+        match self {
+            Stream::Inet(s) => s.writable().await,
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => s.writable().await,
+            #[cfg(windows)]
+            Stream::Pipe(s) => s.writable().await,
+            #[cfg(windows)]
+            Stream::PipeServer(s) => s.writable().await,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("writable", self.variant()).into()),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("writable", self.variant()).into()),
+        }
+    }
+
+    /// Polls for read readiness, for use in hand-written futures.
+    ///
+    /// Like [`Stream::readable`], only the last `cx` passed to this function is woken.
+    /// Usually paired with [`Stream::try_read`].
+    pub fn poll_read_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self {
+            Stream::Inet(s) => s.poll_read_ready(cx),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => s.poll_read_ready(cx),
+            #[cfg(windows)]
+            Stream::Pipe(s) => s.poll_read_ready(cx),
+            #[cfg(windows)]
+            Stream::PipeServer(s) => s.poll_read_ready(cx),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Poll::Ready(Err(unsupported("poll_read_ready", self.variant()).into())),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Poll::Ready(Err(unsupported("poll_read_ready", self.variant()).into())),
+        }
+    }
+
+    /// Polls for write readiness, for use in hand-written futures.
+    ///
+    /// Like [`Stream::writable`], only the last `cx` passed to this function is woken.
+    /// Usually paired with [`Stream::try_write`].
+    pub fn poll_write_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self {
+            Stream::Inet(s) => s.poll_write_ready(cx),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => s.poll_write_ready(cx),
+            #[cfg(windows)]
+            Stream::Pipe(s) => s.poll_write_ready(cx),
+            #[cfg(windows)]
+            Stream::PipeServer(s) => s.poll_write_ready(cx),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Poll::Ready(Err(unsupported("poll_write_ready", self.variant()).into())),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Poll::Ready(Err(unsupported("poll_write_ready", self.variant()).into())),
+        }
+    }
+
+    /// Tries to read data from the stream into the provided buffer, returning how many bytes were read.
+    ///
+    /// If no data is available, an error of kind [`io::ErrorKind::WouldBlock`] is returned.
+    pub fn try_read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Inet(s) => s.try_read(buf),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => s.try_read(buf),
+            #[cfg(windows)]
+            Stream::Pipe(s) => s.try_read(buf),
+            #[cfg(windows)]
+            Stream::PipeServer(s) => s.try_read(buf),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("try_read", self.variant()).into()),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("try_read", self.variant()).into()),
+        }
+    }
+
+    /// Tries to write a buffer to the stream, returning how many bytes were written.
+    ///
+    /// If the stream is not ready to write data, an error of kind [`io::ErrorKind::WouldBlock`] is returned.
+    pub fn try_write(&self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Inet(s) => s.try_write(buf),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => s.try_write(buf),
+            #[cfg(windows)]
+            Stream::Pipe(s) => s.try_write(buf),
+            #[cfg(windows)]
+            Stream::PipeServer(s) => s.try_write(buf),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("try_write", self.variant()).into()),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("try_write", self.variant()).into()),
+        }
+    }
+
+    /// Receives data on the socket from the remote address to which it is connected,
+    /// without removing that data from the queue.
+    ///
+    /// On success, returns the number of bytes peeked.
+    /// Successive calls return the same data.
+    /// Named pipes and vsock streams return an error of kind [`io::ErrorKind::Unsupported`].
+    pub async fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Inet(s) => s.peek(buf).await,
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => {
+                s.async_io(Interest::READABLE, || {
+                    // SAFETY: `peek` only writes initialized bytes into the buffer
+                    let buf = unsafe {
+                        &mut *(buf as *mut [u8] as *mut [std::mem::MaybeUninit<u8>])
+                    };
+                    SockRef::from(s).peek(buf)
+                })
+                .await
+            }
+            #[cfg(windows)]
+            Stream::Pipe(_) | Stream::PipeServer(_) => Err(unsupported("peek", self.variant()).into()),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("peek", self.variant()).into()),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("peek", self.variant()).into()),
+        }
+    }
+
+    /// Returns effective credentials of the process which called `connect` or `pair`.
+    ///
+    /// TCP sockets carry no credentials and return [`Error::Unsupported`](crate::Error::Unsupported).
+    #[cfg(all(unix, feature = "unix"))]
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+    pub fn peer_cred(&self) -> crate::Result<UCred> {
+        match self {
+            Stream::Unix(s) => Ok(s.peer_cred()?),
+            _ => Err(unsupported("peer_cred", self.variant())),
+        }
+    }
+
+    /// Gets the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// Unix and vsock sockets have no such option and return [`Error::Unsupported`](crate::Error::Unsupported).
+    pub fn nodelay(&self) -> crate::Result<bool> {
+        match self {
+            Stream::Inet(s) => Ok(s.nodelay()?),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(_) => Err(unsupported("TCP_NODELAY", self.variant())),
+            #[cfg(windows)]
+            Stream::Pipe(_) | Stream::PipeServer(_) => Err(unsupported("TCP_NODELAY", self.variant())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("TCP_NODELAY", self.variant())),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("TCP_NODELAY", self.variant())),
+        }
+    }
+
+    /// Sets the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// If set, segments are always sent as soon as possible (Nagle's algorithm is disabled).
+    /// Unix and vsock sockets have no such option and return [`Error::Unsupported`](crate::Error::Unsupported).
+    pub fn set_nodelay(&self, nodelay: bool) -> crate::Result<()> {
+        match self {
+            Stream::Inet(s) => Ok(s.set_nodelay(nodelay)?),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(_) => Err(unsupported("TCP_NODELAY", self.variant())),
+            #[cfg(windows)]
+            Stream::Pipe(_) | Stream::PipeServer(_) => Err(unsupported("TCP_NODELAY", self.variant())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("TCP_NODELAY", self.variant())),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("TCP_NODELAY", self.variant())),
+        }
+    }
+
+    /// Corks (`true`) or uncorks (`false`) this socket, to send several writes as few full segments.
+    ///
+    /// While corked, partial segments are held back, even if [`Stream::set_nodelay`] is set.
+    /// Uncorking sends whatever is pending right away.
+    /// This is `TCP_CORK` on Linux and `TCP_NOPUSH` on BSD and macOS.
+    /// Other platforms, Unix and vsock sockets return [`Error::Unsupported`](crate::Error::Unsupported).
+    pub fn set_cork(&self, on: bool) -> crate::Result<()> {
+        match self {
+            #[cfg(any(
+                target_os = "linux",
+                target_os = "android",
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "freebsd"
+            ))]
+            Stream::Inet(s) => {
+                #[cfg(any(target_os = "linux", target_os = "android"))]
+                let opt = libc::TCP_CORK;
+                #[cfg(not(any(target_os = "linux", target_os = "android")))]
+                let opt = libc::TCP_NOPUSH;
+                let v = on as libc::c_int;
+                let r = unsafe {
+                    libc::setsockopt(
+                        s.as_raw_fd(),
+                        libc::IPPROTO_TCP,
+                        opt,
+                        &v as *const libc::c_int as *const libc::c_void,
+                        std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+                    )
+                };
+                if r == -1 {
+                    return Err(io::Error::last_os_error().into());
+                }
+                Ok(())
+            }
+            // there might be no other streams
+            #[allow(unreachable_patterns)]
+            _ => {
+                let _ = on;
+                Err(unsupported("TCP_CORK", self.variant()))
+            }
+        }
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    ///
+    /// Unix and vsock sockets have no such option and return [`Error::Unsupported`](crate::Error::Unsupported).
+    pub fn ttl(&self) -> crate::Result<u32> {
+        match self {
+            Stream::Inet(s) => Ok(s.ttl()?),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(_) => Err(unsupported("IP_TTL", self.variant())),
+            #[cfg(windows)]
+            Stream::Pipe(_) | Stream::PipeServer(_) => Err(unsupported("IP_TTL", self.variant())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("IP_TTL", self.variant())),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("IP_TTL", self.variant())),
+        }
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet sent from this socket.
+    /// Unix and vsock sockets have no such option and return [`Error::Unsupported`](crate::Error::Unsupported).
+    pub fn set_ttl(&self, ttl: u32) -> crate::Result<()> {
+        match self {
+            Stream::Inet(s) => Ok(s.set_ttl(ttl)?),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(_) => Err(unsupported("IP_TTL", self.variant())),
+            #[cfg(windows)]
+            Stream::Pipe(_) | Stream::PipeServer(_) => Err(unsupported("IP_TTL", self.variant())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("IP_TTL", self.variant())),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("IP_TTL", self.variant())),
+        }
+    }
+
+    /// Gets the type-of-service byte of this socket, including the DSCP bits.
+    ///
+    /// This is `IP_TOS` for IPv4 and `IPV6_TCLASS` for IPv6 connections.
+    /// Unix and vsock sockets have no such option and return [`Error::Unsupported`](crate::Error::Unsupported),
+    /// as does IPv6 on Windows.
+    pub fn tos(&self) -> crate::Result<u32> {
+        match self {
+            Stream::Inet(s) if s.local_addr()?.is_ipv4() => Ok(SockRef::from(s).tos_v4()?),
+            #[cfg(unix)]
+            Stream::Inet(s) => {
+                let mut v: libc::c_int = 0;
+                let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+                let r = unsafe {
+                    libc::getsockopt(
+                        s.as_raw_fd(),
+                        libc::IPPROTO_IPV6,
+                        libc::IPV6_TCLASS,
+                        &mut v as *mut libc::c_int as *mut libc::c_void,
+                        &mut len,
+                    )
+                };
+                if r == -1 {
+                    return Err(io::Error::last_os_error().into());
+                }
+                Ok(v as u32)
+            }
+            #[cfg(not(unix))]
+            Stream::Inet(_) => Err(unsupported("IPV6_TCLASS", self.variant())),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(_) => Err(unsupported("IP_TOS", self.variant())),
+            #[cfg(windows)]
+            Stream::Pipe(_) | Stream::PipeServer(_) => Err(unsupported("IP_TOS", self.variant())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("IP_TOS", self.variant())),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("IP_TOS", self.variant())),
+        }
+    }
+
+    /// Sets the type-of-service byte of this socket, used to mark traffic for QoS.
+    ///
+    /// The DSCP value goes into the upper six bits, e.g. `46 << 2` for Expedited Forwarding.
+    /// This is `IP_TOS` for IPv4 and `IPV6_TCLASS` for IPv6 connections.
+    /// Unix and vsock sockets have no such option and return [`Error::Unsupported`](crate::Error::Unsupported),
+    /// as does IPv6 on Windows.
+    pub fn set_tos(&self, tos: u32) -> crate::Result<()> {
+        match self {
+            Stream::Inet(s) if s.local_addr()?.is_ipv4() => Ok(SockRef::from(s).set_tos_v4(tos)?),
+            #[cfg(unix)]
+            Stream::Inet(s) => {
+                let v = tos as libc::c_int;
+                let r = unsafe {
+                    libc::setsockopt(
+                        s.as_raw_fd(),
+                        libc::IPPROTO_IPV6,
+                        libc::IPV6_TCLASS,
+                        &v as *const libc::c_int as *const libc::c_void,
+                        std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+                    )
+                };
+                if r == -1 {
+                    return Err(io::Error::last_os_error().into());
+                }
+                Ok(())
+            }
+            #[cfg(not(unix))]
+            Stream::Inet(_) => Err(unsupported("IPV6_TCLASS", self.variant())),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(_) => Err(unsupported("IP_TOS", self.variant())),
+            #[cfg(windows)]
+            Stream::Pipe(_) | Stream::PipeServer(_) => Err(unsupported("IP_TOS", self.variant())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("IP_TOS", self.variant())),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("IP_TOS", self.variant())),
+        }
+    }
+
+    /// Gets the value of the `SO_PRIORITY` option on this socket.
+    ///
+    /// This is only available on Linux.
+    /// Elsewhere, and for mock streams, [`Error::Unsupported`](crate::Error::Unsupported) is returned.
+    pub fn priority(&self) -> crate::Result<u32> {
+        match self {
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("SO_PRIORITY", self.variant())),
+            #[cfg(target_os = "linux")]
+            _ => {
+                let mut v: libc::c_int = 0;
+                let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+                let r = unsafe {
+                    libc::getsockopt(
+                        self.raw_fd()?,
+                        libc::SOL_SOCKET,
+                        libc::SO_PRIORITY,
+                        &mut v as *mut libc::c_int as *mut libc::c_void,
+                        &mut len,
+                    )
+                };
+                if r == -1 {
+                    return Err(io::Error::last_os_error().into());
+                }
+                Ok(v as u32)
+            }
+            #[cfg(not(target_os = "linux"))]
+            _ => Err(unsupported("SO_PRIORITY", self.variant())),
+        }
+    }
+
+    /// Sets the `SO_PRIORITY` option on this socket, which selects the queue of outgoing packets.
+    ///
+    /// Priorities above 6 require `CAP_NET_ADMIN`.
+    /// This is only available on Linux.
+    /// Elsewhere, and for mock streams, [`Error::Unsupported`](crate::Error::Unsupported) is returned.
+    pub fn set_priority(&self, priority: u32) -> crate::Result<()> {
+        match self {
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("SO_PRIORITY", self.variant())),
+            #[cfg(target_os = "linux")]
+            _ => {
+                let v = priority as libc::c_int;
+                let r = unsafe {
+                    libc::setsockopt(
+                        self.raw_fd()?,
+                        libc::SOL_SOCKET,
+                        libc::SO_PRIORITY,
+                        &v as *const libc::c_int as *const libc::c_void,
+                        std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+                    )
+                };
+                if r == -1 {
+                    return Err(io::Error::last_os_error().into());
+                }
+                Ok(())
+            }
+            #[cfg(not(target_os = "linux"))]
+            _ => {
+                let _ = priority;
+                Err(unsupported("SO_PRIORITY", self.variant()))
+            }
+        }
+    }
+
+    /// Enables (`Some`) or disables (`None`) TCP keepalive probes on this socket.
+    ///
+    /// Unix and vsock sockets have no such option and return [`Error::Unsupported`](crate::Error::Unsupported).
+    pub fn set_keepalive(&self, params: Option<TcpKeepalive>) -> crate::Result<()> {
+        match self {
+            Stream::Inet(s) => {
+                let s = SockRef::from(s);
+                match params {
+                    Some(params) => s.set_tcp_keepalive(&params)?,
+                    None => s.set_keepalive(false)?,
+                }
+                Ok(())
+            }
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(_) => Err(unsupported("SO_KEEPALIVE", self.variant())),
+            #[cfg(windows)]
+            Stream::Pipe(_) | Stream::PipeServer(_) => Err(unsupported("SO_KEEPALIVE", self.variant())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("SO_KEEPALIVE", self.variant())),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("SO_KEEPALIVE", self.variant())),
+        }
+    }
+
+    /// Reads the linger duration for this socket by getting the `SO_LINGER` option.
+    ///
+    /// This and the following socket options return [`Error::Unsupported`](crate::Error::Unsupported) for named pipes and vsock streams.
+    pub fn linger(&self) -> crate::Result<Option<Duration>> {
+        Ok(self.sock_ref()?.linger()?)
+    }
+
+    /// Sets the linger duration of this socket by setting the `SO_LINGER` option.
+    ///
+    /// Works for both TCP and Unix sockets.
+    /// Note that a non-zero linger duration can block the thread on drop
+    /// until all pending data has been sent or the timeout expires.
+    pub fn set_linger(&self, dur: Option<Duration>) -> crate::Result<()> {
+        Ok(self.sock_ref()?.set_linger(dur)?)
+    }
+
+    /// Gets the size of the send buffer (`SO_SNDBUF`).
+    pub fn send_buffer_size(&self) -> crate::Result<usize> {
+        Ok(self.sock_ref()?.send_buffer_size()?)
+    }
+
+    /// Sets the size of the send buffer (`SO_SNDBUF`).
+    ///
+    /// The OS may round the value or clamp it to its limits. Linux doubles it for bookkeeping overhead.
+    pub fn set_send_buffer_size(&self, size: usize) -> crate::Result<()> {
+        Ok(self.sock_ref()?.set_send_buffer_size(size)?)
+    }
+
+    /// Gets the size of the receive buffer (`SO_RCVBUF`).
+    pub fn recv_buffer_size(&self) -> crate::Result<usize> {
+        Ok(self.sock_ref()?.recv_buffer_size()?)
+    }
+
+    /// Sets the size of the receive buffer (`SO_RCVBUF`).
+    ///
+    /// The OS may round the value or clamp it to its limits. Linux doubles it for bookkeeping overhead.
+    pub fn set_recv_buffer_size(&self, size: usize) -> crate::Result<()> {
+        Ok(self.sock_ref()?.set_recv_buffer_size(size)?)
+    }
+
+    /// Shuts down the write direction of this socket.
+    ///
+    /// The peer sees EOF, while this side can still read.
+    /// Unlike [`AsyncWriteExt::shutdown`](tokio::io::AsyncWriteExt::shutdown),
+    /// this does not need `&mut self`.
+    pub fn shutdown_write(&self) -> crate::Result<()> {
+        Ok(self.sock_ref()?.shutdown(std::net::Shutdown::Write)?)
+    }
+
+    /// Shuts down the read direction of this socket.
+    ///
+    /// Further reads return EOF.
+    pub fn shutdown_read(&self) -> crate::Result<()> {
+        Ok(self.sock_ref()?.shutdown(std::net::Shutdown::Read)?)
+    }
+
+    /// Writes all of `bufs`, like [`AsyncWriteExt::write_all`](tokio::io::AsyncWriteExt::write_all)
+    /// does for a single buffer.
+    ///
+    /// Partial writes are continued with the remaining bytes, modifying `bufs` on the way.
+    /// If the stream does not accept any more data, an error of kind
+    /// [`io::ErrorKind::WriteZero`] is returned.
+    pub async fn write_all_vectored(&mut self, mut bufs: &mut [io::IoSlice<'_>]) -> io::Result<()> {
+        use tokio::io::AsyncWriteExt;
+        io::IoSlice::advance_slices(&mut bufs, 0);
+        while !bufs.is_empty() {
+            match self.write_vectored(bufs).await? {
+                0 => return Err(io::ErrorKind::WriteZero.into()),
+                n => io::IoSlice::advance_slices(&mut bufs, n),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks without blocking whether the peer has closed or shut down its write direction.
+    ///
+    /// This peeks at the socket, so no data is consumed.
+    /// If the peer sent data before closing, that data has to be read before the close is seen,
+    /// and this returns `false` until then.
+    /// A reset connection returns its error.
+    /// Named pipes and vsock streams return [`Error::Unsupported`](crate::Error::Unsupported).
+    pub fn is_peer_closed(&self) -> crate::Result<bool> {
+        let mut buf = [std::mem::MaybeUninit::uninit()];
+        match self.sock_ref()?.peek(&mut buf) {
+            Ok(n) => Ok(n == 0),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Checks without blocking that nothing is waiting to be read, not even EOF.
+    pub(crate) fn is_idle(&self) -> crate::Result<bool> {
+        let mut buf = [std::mem::MaybeUninit::uninit()];
+        match self.sock_ref()?.peek(&mut buf) {
+            Ok(_) => Ok(false),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(true),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Flushes and then shuts down the write direction of this stream.
+    ///
+    /// Once this returns, the peer sees EOF after all data written so far.
+    /// This side can still read, the socket itself is closed on drop.
+    pub async fn close(&mut self) -> io::Result<()> {
+        use tokio::io::AsyncWriteExt;
+        self.flush().await?;
+        self.shutdown().await
+    }
+
+    /// Copies data between `a` and `b` in both directions until both sides are done,
+    /// e.g. to forward a client connection to a backend.
+    ///
+    /// Once one side sends EOF, the write direction of the other side is shut down,
+    /// while data still flows the other way.
+    /// Returns the number of bytes copied from `a` to `b` and from `b` to `a`.
+    /// See [`tokio::io::copy_bidirectional`].
+    pub async fn proxy(mut a: Stream, mut b: Stream) -> io::Result<(u64, u64)> {
+        tokio::io::copy_bidirectional(&mut a, &mut b).await
+    }
+
+    /// Moves this socket into or out of nonblocking mode (`O_NONBLOCK` / `FIONBIO`).
+    ///
+    /// Tokio requires nonblocking sockets. Only switch to blocking mode right before
+    /// handing the socket off to code that does its own (blocking) I/O,
+    /// and switch back before using this `Stream` again.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> crate::Result<()> {
+        Ok(self.sock_ref()?.set_nonblocking(nonblocking)?)
+    }
+
+    /// Returns the file descriptor of the socket.
+    ///
+    /// Unlike [`AsRawFd`], this works with the `test-util` feature enabled:
+    /// mock streams return [`Error::Unsupported`](crate::Error::Unsupported).
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub fn raw_fd(&self) -> crate::Result<RawFd> {
+        match self {
+            Stream::Inet(s) => Ok(s.as_raw_fd()),
+            #[cfg(feature = "unix")]
+            Stream::Unix(s) => Ok(s.as_raw_fd()),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(s) => Ok(s.as_raw_fd()),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("file descriptor", self.variant())),
+        }
+    }
+
+    /// Borrow the underlying socket to access socket options
+    fn sock_ref(&self) -> crate::Result<SockRef<'_>> {
+        match self {
+            Stream::Inet(s) => Ok(SockRef::from(s)),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => Ok(SockRef::from(s)),
+            #[cfg(windows)]
+            Stream::Pipe(_) | Stream::PipeServer(_) => Err(unsupported("socket options", self.variant())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(unsupported("socket options", self.variant())),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(_) => Err(unsupported("socket options", self.variant())),
         }
     }
 }
@@ -68,8 +1093,16 @@ impl AsyncRead for Stream {
     ) -> Poll<Result<(), Error>> {
         match &mut *self {
             Stream::Inet(s) => Pin::new(s).as_mut().poll_read(cx, buf),
-            #[cfg(unix)]
+            #[cfg(all(unix, feature = "unix"))]
             Stream::Unix(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+            #[cfg(windows)]
+            Stream::Pipe(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+            #[cfg(windows)]
+            Stream::PipeServer(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(s) => Pin::new(s).as_mut().poll_read(cx, buf),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(s) => Pin::new(s).as_mut().poll_read(cx, buf),
         }
     }
 }
@@ -81,29 +1114,123 @@ impl AsyncWrite for Stream {
     ) -> Poll<Result<usize, Error>> {
         match &mut *self {
             Stream::Inet(s) => Pin::new(s).as_mut().poll_write(cx, buf),
-            #[cfg(unix)]
+            #[cfg(all(unix, feature = "unix"))]
             Stream::Unix(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+            #[cfg(windows)]
+            Stream::Pipe(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+            #[cfg(windows)]
+            Stream::PipeServer(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(s) => Pin::new(s).as_mut().poll_write(cx, buf),
+        }
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<Result<usize, Error>> {
+        match &mut *self {
+            Stream::Inet(s) => Pin::new(s).as_mut().poll_write_vectored(cx, bufs),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => Pin::new(s).as_mut().poll_write_vectored(cx, bufs),
+            #[cfg(windows)]
+            Stream::Pipe(s) => Pin::new(s).as_mut().poll_write_vectored(cx, bufs),
+            #[cfg(windows)]
+            Stream::PipeServer(s) => Pin::new(s).as_mut().poll_write_vectored(cx, bufs),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(s) => Pin::new(s).as_mut().poll_write_vectored(cx, bufs),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(s) => Pin::new(s).as_mut().poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            Stream::Inet(s) => s.is_write_vectored(),
+            #[cfg(all(unix, feature = "unix"))]
+            Stream::Unix(s) => s.is_write_vectored(),
+            #[cfg(windows)]
+            Stream::Pipe(s) => s.is_write_vectored(),
+            #[cfg(windows)]
+            Stream::PipeServer(s) => s.is_write_vectored(),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(s) => s.is_write_vectored(),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(s) => s.is_write_vectored(),
         }
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
         match &mut *self {
             Stream::Inet(s) => Pin::new(s).as_mut().poll_flush(cx),
-            #[cfg(unix)]
+            #[cfg(all(unix, feature = "unix"))]
             Stream::Unix(s) => Pin::new(s).as_mut().poll_flush(cx),
+            #[cfg(windows)]
+            Stream::Pipe(s) => Pin::new(s).as_mut().poll_flush(cx),
+            #[cfg(windows)]
+            Stream::PipeServer(s) => Pin::new(s).as_mut().poll_flush(cx),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(s) => Pin::new(s).as_mut().poll_flush(cx),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(s) => Pin::new(s).as_mut().poll_flush(cx),
         }
     }
 
     fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
-        match &mut *self {
+        let r = match &mut *self {
             Stream::Inet(s) => Pin::new(s).as_mut().poll_shutdown(cx),
-            #[cfg(unix)]
+            #[cfg(all(unix, feature = "unix"))]
             Stream::Unix(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+            #[cfg(windows)]
+            Stream::Pipe(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+            #[cfg(windows)]
+            Stream::PipeServer(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+            #[cfg(feature = "test-util")]
+            Stream::Mock(s) => Pin::new(s).as_mut().poll_shutdown(cx),
+        };
+        if let Poll::Ready(Ok(())) = r {
+            crate::trace::closed(&self);
         }
+        r
     }
 }
 
+/// Not available with the `test-util` feature, as [`Stream::Mock`] has no fd
+#[cfg(all(unix, not(feature = "test-util")))]
+impl AsRawFd for Stream {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            Stream::Inet(s) => s.as_raw_fd(),
+            #[cfg(feature = "unix")]
+            Stream::Unix(s) => s.as_raw_fd(),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(s) => s.as_raw_fd(),
+        }
+    }
+}
+/// Not available with the `test-util` feature, as [`Stream::Mock`] has no fd
+#[cfg(all(unix, not(feature = "test-util")))]
+impl AsFd for Stream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the fd stays open as long as `self` is borrowed
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
 
+/// A blocking std stream, returned by [`Stream::into_std`].
+#[derive(Debug)]
+pub enum StdStream {
+    /// A TCP connection
+    Inet(std::net::TcpStream),
+    #[cfg(all(unix, feature = "unix"))]
+    /// A Unix socket connection
+    Unix(std::os::unix::net::UnixStream),
+}
 
 #[cfg(test)]
 pub(crate) mod tests {
@@ -111,7 +1238,7 @@ pub(crate) mod tests {
     use std::net::SocketAddr;
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
     use tokio::net::TcpListener;
-    #[cfg(unix)]
+    #[cfg(all(unix, feature = "unix"))]
     use tokio::net::UnixListener;
     use tokio::runtime::Builder;
 
@@ -122,6 +1249,309 @@ pub(crate) mod tests {
         Ok((app_listener, a))
     }
 
+    /// A connected pair of TCP streams: (client, server)
+    pub(crate) async fn tcp_pair() -> Result<(Stream, Stream), std::io::Error> {
+        let (app_listener, a) = local_socket_pair().await?;
+        let c = Stream::connect(&a).await?;
+        let (s, _) = app_listener.accept().await?;
+        Ok((c, s.into()))
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn tcp_connect_from() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (l, a) = local_socket_pair().await.unwrap();
+            let local: Addr = "127.0.0.1:0".parse().unwrap();
+            let c = Stream::connect_from(&local, &a).await.unwrap();
+            let (_, peer) = l.accept().await.unwrap();
+            let local = c.local_addr().unwrap();
+            assert_eq!(Addr::Inet(peer), local);
+            assert_eq!(
+                local.as_inet().unwrap().ip(),
+                "127.0.0.1".parse::<std::net::IpAddr>().unwrap()
+            );
+
+            let v6: Addr = "[::1]:0".parse().unwrap();
+            let e = Stream::connect_from(&v6, &a).await.unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_connect_from() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let _ = std::fs::remove_file("/tmp/asc_from_srv.sock");
+            let _ = std::fs::remove_file("/tmp/asc_from_cli.sock");
+            let l = UnixListener::bind("/tmp/asc_from_srv.sock").unwrap();
+            let local: Addr = "/tmp/asc_from_cli.sock".parse().unwrap();
+            let remote: Addr = "/tmp/asc_from_srv.sock".parse().unwrap();
+            let _c = Stream::connect_from(&local, &remote).await.unwrap();
+            let (_, peer) = l.accept().await.unwrap();
+            assert_eq!(Addr::from(peer), local);
+            std::fs::remove_file("/tmp/asc_from_srv.sock").unwrap();
+            std::fs::remove_file("/tmp/asc_from_cli.sock").unwrap();
+        });
+    }
+    #[test]
+    fn tcp_connect_timeout() {
+        use socket2::{Domain, Socket, Type};
+
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            // nobody accepts and the backlog is tiny:
+            // once it is full, the SYNs are dropped and connect hangs
+            let l = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+            l.bind(&"127.0.0.1:0".parse::<SocketAddr>().unwrap().into())
+                .unwrap();
+            l.listen(0).unwrap();
+            let a: Addr = l.local_addr().unwrap().as_socket().unwrap().into();
+
+            let mut established = Vec::new();
+            for _ in 0..8 {
+                match Stream::connect_timeout(&a, Duration::from_millis(100)).await {
+                    Ok(s) => established.push(s),
+                    Err(e) => {
+                        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+                        return;
+                    }
+                }
+            }
+            panic!("connect never timed out");
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn peer_cred() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let _ = std::fs::remove_file("/tmp/asc_peer_cred.sock");
+            let l = UnixListener::bind("/tmp/asc_peer_cred.sock").unwrap();
+            let a: Addr = "/tmp/asc_peer_cred.sock".parse().unwrap();
+            let c = Stream::connect(&a).await.unwrap();
+            let (s, _) = l.accept().await.unwrap();
+            let s = Stream::from(s);
+            let cred = s.peer_cred().unwrap();
+            assert_eq!(cred.uid(), unsafe { libc::getuid() });
+            assert_eq!(cred.gid(), unsafe { libc::getgid() });
+            drop(c);
+            std::fs::remove_file("/tmp/asc_peer_cred.sock").unwrap();
+
+            let (c, _s) = tcp_pair().await.unwrap();
+            assert_eq!(c.peer_cred().unwrap_err().kind(), io::ErrorKind::Unsupported);
+        });
+    }
+    async fn peek_then_read(mut c: Stream, mut s: Stream) {
+        c.write_all(b"1234").await.unwrap();
+        let mut buf = [0u8; 4];
+        let mut n = 0;
+        while n < 4 {
+            n = s.peek(&mut buf).await.unwrap();
+        }
+        assert_eq!(&buf, b"1234");
+        let mut buf = [0u8; 4];
+        s.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"1234");
+    }
+    #[test]
+    fn tcp_peek() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = tcp_pair().await.unwrap();
+            peek_then_read(c, s).await;
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_peek() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = UnixStream::pair().unwrap();
+            peek_then_read(c.into(), s.into()).await;
+        });
+    }
+    #[test]
+    fn happy_eyeballs() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (app_listener, a) = local_socket_pair().await.unwrap();
+            let port = a.as_inet().unwrap().port();
+            let c = Stream::connect_happy_eyeballs("localhost", port)
+                .await
+                .expect("no address family connected");
+            let (s, _) = app_listener.accept().await.unwrap();
+            assert_eq!(c.local_addr().unwrap(), Addr::Inet(s.peer_addr().unwrap()));
+        });
+    }
+    async fn try_write_read(c: Stream, s: Stream) {
+        c.writable().await.unwrap();
+        assert_eq!(c.try_write(b"1234").unwrap(), 4);
+        let mut buf = [0u8; 4];
+        let mut n = 0;
+        while n < 4 {
+            s.readable().await.unwrap();
+            match s.try_read(&mut buf[n..]) {
+                Ok(i) => n += i,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => panic!("{}", e),
+            }
+        }
+        assert_eq!(&buf, b"1234");
+        assert_eq!(
+            s.try_read(&mut buf).unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+        assert!(c.ready(Interest::WRITABLE).await.unwrap().is_writable());
+    }
+    #[test]
+    fn tcp_try_write() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = tcp_pair().await.unwrap();
+            try_write_read(c, s).await;
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_try_write() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = UnixStream::pair().unwrap();
+            try_write_read(c.into(), s.into()).await;
+        });
+    }
+    async fn write_vectored(mut c: Stream, mut s: Stream) {
+        assert!(c.is_write_vectored());
+        let bufs = [
+            io::IoSlice::new(b"12"),
+            io::IoSlice::new(b"34"),
+            io::IoSlice::new(b"56"),
+        ];
+        let n = c.write_vectored(&bufs).await.unwrap();
+        assert_eq!(n, 6);
+        let mut buf = [0u8; 6];
+        s.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"123456");
+    }
+    #[test]
+    fn tcp_write_vectored() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = tcp_pair().await.unwrap();
+            write_vectored(c, s).await;
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_write_vectored() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = UnixStream::pair().unwrap();
+            write_vectored(c.into(), s.into()).await;
+        });
+    }
+    #[test]
+    fn tcp_nodelay() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = tcp_pair().await.unwrap();
+            c.set_nodelay(true).unwrap();
+            assert!(c.nodelay().unwrap());
+            c.set_nodelay(false).unwrap();
+            assert!(!c.nodelay().unwrap());
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_nodelay() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = UnixStream::pair().unwrap();
+            let c = Stream::from(c);
+            assert_eq!(
+                c.set_nodelay(true).unwrap_err().kind(),
+                io::ErrorKind::Unsupported
+            );
+            assert_eq!(c.nodelay().unwrap_err().kind(), io::ErrorKind::Unsupported);
+            match c.nodelay().unwrap_err() {
+                e @ crate::Error::Unsupported { .. } => {
+                    assert_eq!(e.to_string(), "TCP_NODELAY is not supported by Stream::Unix")
+                }
+                e => panic!("{:?}", e),
+            }
+        });
+    }
+    #[test]
+    fn tcp_ttl() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = tcp_pair().await.unwrap();
+            c.set_ttl(64).unwrap();
+            assert_eq!(c.ttl().unwrap(), 64);
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_ttl() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = UnixStream::pair().unwrap();
+            let c = Stream::from(c);
+            assert_eq!(c.set_ttl(64).unwrap_err().kind(), io::ErrorKind::Unsupported);
+            assert_eq!(c.ttl().unwrap_err().kind(), io::ErrorKind::Unsupported);
+        });
+    }
+    #[test]
+    fn tcp_keepalive() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = tcp_pair().await.unwrap();
+            c.set_keepalive(Some(TcpKeepalive::new().with_time(Duration::from_secs(10))))
+                .unwrap();
+            assert!(c.sock_ref().unwrap().keepalive().unwrap());
+            c.set_keepalive(None).unwrap();
+            assert!(!c.sock_ref().unwrap().keepalive().unwrap());
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_keepalive() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = UnixStream::pair().unwrap();
+            let c = Stream::from(c);
+            assert_eq!(
+                c.set_keepalive(None).unwrap_err().kind(),
+                io::ErrorKind::Unsupported
+            );
+        });
+    }
+    #[test]
+    fn tcp_linger() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = tcp_pair().await.unwrap();
+            c.set_linger(Some(Duration::from_secs(5))).unwrap();
+            assert_eq!(c.linger().unwrap(), Some(Duration::from_secs(5)));
+            c.set_linger(None).unwrap();
+            assert_eq!(c.linger().unwrap(), None);
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_linger() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = UnixStream::pair().unwrap();
+            let c = Stream::from(c);
+            c.set_linger(Some(Duration::from_secs(1))).unwrap();
+            assert_eq!(c.linger().unwrap(), Some(Duration::from_secs(1)));
+            c.set_linger(None).unwrap();
+            assert_eq!(c.linger().unwrap(), None);
+        });
+    }
     #[test]
     fn tcp_connect() {
         let rt = Builder::new_current_thread().enable_all().build().unwrap();
@@ -147,7 +1577,7 @@ pub(crate) mod tests {
         }
         rt.block_on(con());
     }
-    #[cfg(unix)]
+    #[cfg(all(unix, feature = "unix"))]
     #[test]
     fn unix_connect() {
         use std::path::Path;
@@ -178,4 +1608,382 @@ pub(crate) mod tests {
         rt.block_on(con());
         std::fs::remove_file("/tmp/afcgi.sock").unwrap();
     }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn raw_fd() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = tcp_pair().await.unwrap();
+            let mut ty: libc::c_int = 0;
+            let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+            let r = unsafe {
+                libc::getsockopt(
+                    c.raw_fd().unwrap(),
+                    libc::SOL_SOCKET,
+                    libc::SO_TYPE,
+                    &mut ty as *mut libc::c_int as *mut libc::c_void,
+                    &mut len,
+                )
+            };
+            assert_eq!(r, 0);
+            assert_eq!(ty, libc::SOCK_STREAM);
+            #[cfg(not(feature = "test-util"))]
+            {
+                assert_eq!(c.as_raw_fd(), c.raw_fd().unwrap());
+                assert_eq!(c.as_fd().as_raw_fd(), c.as_raw_fd());
+            }
+        });
+    }
+    #[test]
+    fn tcp_buffer_size() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = tcp_pair().await.unwrap();
+            c.set_recv_buffer_size(128 * 1024).unwrap();
+            assert!(c.recv_buffer_size().unwrap() >= 128 * 1024);
+            c.set_send_buffer_size(128 * 1024).unwrap();
+            assert!(c.send_buffer_size().unwrap() >= 128 * 1024);
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn unix_buffer_size() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = UnixStream::pair().unwrap();
+            let c = Stream::from(c);
+            c.set_recv_buffer_size(128 * 1024).unwrap();
+            assert!(c.recv_buffer_size().unwrap() >= 128 * 1024);
+        });
+    }
+    #[test]
+    fn shutdown_write() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            c.write_all(b"request").await.unwrap();
+            c.shutdown_write().unwrap();
+            let mut buf = Vec::new();
+            s.read_to_end(&mut buf).await.unwrap();
+            assert_eq!(buf, b"request");
+            s.write_all(b"response").await.unwrap();
+            drop(s);
+            let mut buf = Vec::new();
+            c.read_to_end(&mut buf).await.unwrap();
+            assert_eq!(buf, b"response");
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn connect_to_path() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let path = std::path::Path::new("/tmp/asc_connect_to.sock");
+            let _ = std::fs::remove_file(path);
+            let l = UnixListener::bind(path).unwrap();
+            let (c, s) = tokio::join!(Stream::connect_to(path), l.accept());
+            assert!(matches!(c.unwrap(), Stream::Unix(_)));
+            s.unwrap();
+            std::fs::remove_file(path).unwrap();
+        });
+    }
+    #[test]
+    fn tos() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = tcp_pair().await.unwrap();
+            c.set_tos(0x10).unwrap();
+            assert_eq!(c.tos().unwrap(), 0x10);
+        });
+    }
+    #[test]
+    fn is_peer_closed() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = tcp_pair().await.unwrap();
+            assert!(!c.is_peer_closed().unwrap());
+            s.shutdown_write().unwrap();
+            c.readable().await.unwrap();
+            assert!(c.is_peer_closed().unwrap());
+        });
+    }
+    #[test]
+    fn pair() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut a, mut b) = Stream::pair().unwrap();
+            a.write_all(b"ping").await.unwrap();
+            let mut buf = [0u8; 4];
+            b.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"ping");
+        });
+    }
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn mock() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut a, mut b) = Stream::mock(64);
+            a.write_all(b"ping").await.unwrap();
+            let mut buf = [0u8; 4];
+            b.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"ping");
+            b.write_all(b"pong").await.unwrap();
+            a.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"pong");
+            assert_eq!(
+                a.peer_addr().unwrap_err().kind(),
+                std::io::ErrorKind::Unsupported
+            );
+            assert!(matches!(a.is_peer_closed(), Err(crate::Error::Unsupported { .. })));
+            assert!(matches!(a.set_nonblocking(true), Err(crate::Error::Unsupported { .. })));
+            #[cfg(unix)]
+            assert!(matches!(a.raw_fd(), Err(crate::Error::Unsupported { .. })));
+        });
+    }
+    #[test]
+    fn is_loopback() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = tcp_pair().await.unwrap();
+            assert!(c.is_loopback());
+            #[cfg(all(unix, feature = "unix"))]
+            {
+                let (a, _b) = Stream::pair().unwrap();
+                assert!(a.is_loopback());
+            }
+            // the address of the interface with the default route, if there is one
+            let lan = std::net::UdpSocket::bind("0.0.0.0:0")
+                .and_then(|u| u.connect("192.0.2.1:9").and_then(|_| u.local_addr()))
+                .map(|a| a.ip());
+            let lan = match lan {
+                Ok(ip) if !ip.is_loopback() && !ip.is_unspecified() => ip,
+                _ => return,
+            };
+            let l = TcpListener::bind((lan, 0)).await.unwrap();
+            let a = Addr::Inet(l.local_addr().unwrap());
+            let (c, _s) = tokio::join!(Stream::connect(&a), l.accept());
+            assert!(!c.unwrap().is_loopback());
+        });
+    }
+    #[test]
+    fn write_all_vectored() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            let parts: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 256 * 1024]).collect();
+            let reader = tokio::spawn(async move {
+                let mut buf = Vec::new();
+                s.read_to_end(&mut buf).await.unwrap();
+                buf
+            });
+            let mut bufs: Vec<_> = parts.iter().map(|p| io::IoSlice::new(p)).collect();
+            c.write_all_vectored(&mut bufs).await.unwrap();
+            drop(c);
+            assert_eq!(reader.await.unwrap(), parts.concat());
+        });
+    }
+    #[cfg(all(unix, feature = "unix"))]
+    #[test]
+    fn from_raw_fd_checked() {
+        use std::io::Write;
+        use std::os::unix::io::{FromRawFd, IntoRawFd};
+
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (a, mut b) = std::os::unix::net::UnixStream::pair().unwrap();
+            let mut a = unsafe { Stream::from_raw_fd_checked(a.into_raw_fd()) }.unwrap();
+            assert!(matches!(a, Stream::Unix(_)));
+            b.write_all(b"x").unwrap();
+            let mut buf = [0u8; 1];
+            a.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"x");
+
+            let u = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            let fd = u.into_raw_fd();
+            let e = unsafe { Stream::from_raw_fd_checked(fd) }.err().unwrap();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+            // still open and owned by us
+            drop(unsafe { std::net::UdpSocket::from_raw_fd(fd) });
+        });
+    }
+    #[test]
+    fn poll_write_ready() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, s) = tcp_pair().await.unwrap();
+            std::future::poll_fn(|cx| c.poll_write_ready(cx)).await.unwrap();
+            assert_eq!(c.try_write(b"x").unwrap(), 1);
+            std::future::poll_fn(|cx| s.poll_read_ready(cx)).await.unwrap();
+            let mut buf = [0u8; 1];
+            assert_eq!(s.try_read(&mut buf).unwrap(), 1);
+        });
+    }
+    #[test]
+    fn from_std_tcp() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (l, a) = local_socket_pair().await.unwrap();
+            let a = *a.as_inet().unwrap();
+            let std = tokio::task::spawn_blocking(move || std::net::TcpStream::connect(a));
+            let (mut server, _) = l.accept().await.unwrap();
+            let mut c = Stream::from_std_tcp(std.await.unwrap().unwrap()).unwrap();
+            c.write_all(b"ping").await.unwrap();
+            let mut buf = [0u8; 4];
+            server.read_exact(&mut buf).await.unwrap();
+            server.write_all(&buf).await.unwrap();
+            c.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"ping");
+
+            match c.into_std().unwrap() {
+                StdStream::Inet(s) => assert_eq!(s.peer_addr().unwrap(), a),
+                #[cfg(all(unix, feature = "unix"))]
+                _ => unreachable!(),
+            }
+        });
+    }
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd"
+    ))]
+    #[test]
+    fn cork() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            c.set_cork(true).unwrap();
+            c.write_all(b"head").await.unwrap();
+            c.write_all(b"body").await.unwrap();
+            c.set_cork(false).unwrap();
+            let mut buf = [0u8; 8];
+            s.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"headbody");
+        });
+    }
+    #[test]
+    fn connect_to_listener() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            #[allow(unused_mut)]
+            let mut listeners = vec![crate::Listener::bind(&"0.0.0.0:0".parse().unwrap())
+                .await
+                .unwrap()];
+            #[cfg(all(unix, feature = "unix"))]
+            {
+                let path = "/tmp/asc_connect_to_listener.sock";
+                let _ = std::fs::remove_file(path);
+                listeners.push(crate::Listener::bind(&path.parse().unwrap()).await.unwrap());
+            }
+            for l in listeners {
+                let (c, s) = tokio::join!(Stream::connect_to_listener(&l), l.accept());
+                let (mut c, mut s) = (c.unwrap(), s.unwrap().0);
+                c.write_u8(b'x').await.unwrap();
+                let b = s.read_u8().await.unwrap();
+                s.write_u8(b).await.unwrap();
+                assert_eq!(c.read_u8().await.unwrap(), b'x');
+            }
+        });
+    }
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn priority() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = tcp_pair().await.unwrap();
+            c.set_priority(6).unwrap();
+            assert_eq!(c.priority().unwrap(), 6);
+            let (c, _s) = Stream::pair().unwrap();
+            c.set_priority(6).unwrap();
+            assert_eq!(c.priority().unwrap(), 6);
+        });
+    }
+    #[test]
+    fn proxy() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (echo, echo_addr) = local_socket_pair().await.unwrap();
+            tokio::spawn(async move {
+                let (mut s, _) = echo.accept().await.unwrap();
+                let (mut r, mut w) = s.split();
+                tokio::io::copy(&mut r, &mut w).await.unwrap();
+            });
+            let (front, front_addr) = local_socket_pair().await.unwrap();
+            let relay = tokio::spawn(async move {
+                let (s, _) = front.accept().await.unwrap();
+                let backend = Stream::connect(&echo_addr).await.unwrap();
+                Stream::proxy(Stream::Inet(s), backend).await.unwrap()
+            });
+
+            let mut c = Stream::connect(&front_addr).await.unwrap();
+            c.write_all(b"round trip").await.unwrap();
+            c.shutdown().await.unwrap();
+            let mut buf = Vec::new();
+            c.read_to_end(&mut buf).await.unwrap();
+            assert_eq!(buf, b"round trip");
+            assert_eq!(relay.await.unwrap(), (10, 10));
+        });
+    }
+    #[test]
+    fn close() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            c.write_all(b"bye").await.unwrap();
+            c.close().await.unwrap();
+            let mut buf = Vec::new();
+            s.read_to_end(&mut buf).await.unwrap();
+            assert_eq!(buf, b"bye");
+        });
+    }
+    #[test]
+    fn set_nonblocking() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            c.set_nonblocking(false).unwrap();
+            c.set_nonblocking(true).unwrap();
+            c.write_all(b"1").await.unwrap();
+            let mut buf = [0u8; 1];
+            s.read_exact(&mut buf).await.unwrap();
+        });
+    }
+    #[test]
+    fn shutdown_read() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, _s) = tcp_pair().await.unwrap();
+            c.shutdown_read().unwrap();
+            let mut buf = [0u8; 4];
+            assert_eq!(c.read(&mut buf).await.unwrap(), 0);
+        });
+    }
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    #[test]
+    fn vsock_loopback() {
+        // only if there is a vsock device (and the vsock_loopback module is loaded)
+        if !std::path::Path::new("/dev/vsock").exists() {
+            return;
+        }
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            // VMADDR_CID_LOCAL
+            let a = Addr::Vsock { cid: 1, port: 7613 };
+            let l = match crate::Listener::bind(&a).await {
+                Ok(l) => l,
+                // no vsock_loopback module
+                Err(e) if e.kind() == std::io::ErrorKind::AddrNotAvailable => return,
+                Err(e) => panic!("{}", e),
+            };
+            let mut c = Stream::connect(&a).await.unwrap();
+            let (mut s, _) = l.accept().await.unwrap();
+            c.write_all(b"1234").await.unwrap();
+            let mut buf = [0u8; 4];
+            s.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"1234");
+            assert_eq!(c.peer_addr().unwrap(), a);
+        });
+    }
 }
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use crate::{Listener, Stream};
+#[cfg(any(unix, windows))]
+use crate::unsupported;
+
+/// Statistics of a TCP connection, as returned by [`Stream::tcp_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TcpInfo {
+    /// Smoothed round trip time
+    pub rtt: Duration,
+    /// Variance of the round trip time
+    pub rtt_var: Duration,
+    /// Number of segments retransmitted over the whole connection
+    pub retransmits: u32,
+    /// Congestion window, in segments
+    pub snd_cwnd: u32,
+}
+
+impl Stream {
+    /// Reads statistics of a TCP connection, like its round trip time, using `TCP_INFO`.
+    ///
+    /// This is only available on Linux.
+    /// Elsewhere, and for non-TCP streams, [`Error::Unsupported`](crate::Error::Unsupported) is returned.
+    pub fn tcp_info(&self) -> crate::Result<TcpInfo> {
+        match self {
+            #[cfg(target_os = "linux")]
+            Stream::Inet(s) => {
+                let info = raw_tcp_info(s)?;
+                Ok(TcpInfo {
+                    rtt: Duration::from_micros(info.tcpi_rtt.into()),
+                    rtt_var: Duration::from_micros(info.tcpi_rttvar.into()),
+                    retransmits: info.tcpi_total_retrans,
+                    snd_cwnd: info.tcpi_snd_cwnd,
+                })
+            }
+            // there might be no other streams
+            #[allow(unreachable_patterns)]
+            _ => Err(unsupported("TCP_INFO", self.variant())),
+        }
+    }
+}
+
+impl Listener {
+    /// Returns how many connections are waiting to be accepted, using `TCP_INFO`.
+    ///
+    /// This is only available for TCP listeners on Linux.
+    /// Elsewhere [`Error::Unsupported`](crate::Error::Unsupported) is returned.
+    pub fn accept_queue_len(&self) -> crate::Result<u32> {
+        match self {
+            #[cfg(target_os = "linux")]
+            // for listening sockets the kernel reports the accept queue as unacked
+            Listener::Inet(s) => Ok(raw_tcp_info(s)?.tcpi_unacked),
+            // there might be no other listeners
+            #[allow(unreachable_patterns)]
+            _ => Err(unsupported("TCP_INFO", self.variant())),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn raw_tcp_info(s: &impl std::os::unix::io::AsRawFd) -> std::io::Result<libc::tcp_info> {
+    let mut info: libc::tcp_info = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<libc::tcp_info>() as libc::socklen_t;
+    let r = unsafe {
+        libc::getsockopt(
+            s.as_raw_fd(),
+            libc::IPPROTO_TCP,
+            libc::TCP_INFO,
+            &mut info as *mut libc::tcp_info as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if r == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(info)
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use crate::stream::tests::tcp_pair;
+    use crate::{Listener, Stream};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn loopback() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut c, mut s) = tcp_pair().await.unwrap();
+            // get an ACK, so that there is an RTT sample
+            c.write_all(b"ping").await.unwrap();
+            let mut buf = [0u8; 4];
+            s.read_exact(&mut buf).await.unwrap();
+            s.write_all(b"pong").await.unwrap();
+            c.read_exact(&mut buf).await.unwrap();
+            let info = c.tcp_info().unwrap();
+            assert!(!info.rtt.is_zero());
+            assert!(info.snd_cwnd > 0);
+        });
+    }
+    #[test]
+    fn accept_queue_len() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+            assert_eq!(l.accept_queue_len().unwrap(), 0);
+            let _c = Stream::connect(&l.local_addr().unwrap()).await.unwrap();
+            assert_eq!(l.accept_queue_len().unwrap(), 1);
+            l.accept().await.unwrap();
+            assert_eq!(l.accept_queue_len().unwrap(), 0);
+        });
+    }
+}
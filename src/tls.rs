@@ -0,0 +1,83 @@
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+use std::io;
+
+use crate::Stream;
+
+/// A TLS session on top of a [`Stream`], either server or client side.
+pub type TlsStream = tokio_rustls::TlsStream<Stream>;
+
+impl Stream {
+    /// Performs the server side of a TLS handshake on this connection.
+    pub async fn accept_tls(self, acceptor: &TlsAcceptor) -> io::Result<TlsStream> {
+        acceptor.accept(self).await.map(TlsStream::from)
+    }
+
+    /// Performs the client side of a TLS handshake on this connection.
+    ///
+    /// The certificate of the server is validated against `domain`.
+    pub async fn connect_tls(
+        self,
+        connector: &TlsConnector,
+        domain: ServerName<'static>,
+    ) -> io::Result<TlsStream> {
+        connector.connect(domain, self).await.map(TlsStream::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::tests::tcp_pair;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::runtime::Builder;
+    use tokio_rustls::rustls::pki_types::PrivateKeyDer;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerConfig};
+
+    fn configs() -> (TlsAcceptor, TlsConnector) {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let key = PrivateKeyDer::Pkcs8(cert.key_pair.serialize_der().into());
+        let cert = cert.cert.der().clone();
+
+        let server = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert.clone()], key)
+            .unwrap();
+        let mut roots = RootCertStore::empty();
+        roots.add(cert).unwrap();
+        let client = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        (
+            TlsAcceptor::from(Arc::new(server)),
+            TlsConnector::from(Arc::new(client)),
+        )
+    }
+
+    #[test]
+    fn tls_echo() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (acceptor, connector) = configs();
+            let (c, s) = tcp_pair().await.unwrap();
+            let server = tokio::spawn(async move {
+                let mut s = s.accept_tls(&acceptor).await.unwrap();
+                let mut buf = [0u8; 1];
+                s.read_exact(&mut buf).await.unwrap();
+                s.write_all(&buf).await.unwrap();
+                s.shutdown().await.unwrap();
+            });
+            let mut c = c
+                .connect_tls(&connector, ServerName::try_from("localhost").unwrap())
+                .await
+                .unwrap();
+            c.write_all(b"1").await.unwrap();
+            let mut buf = Vec::new();
+            c.read_to_end(&mut buf).await.unwrap();
+            assert_eq!(buf, b"1");
+            server.await.unwrap();
+        });
+    }
+}
@@ -0,0 +1,124 @@
+//! Lifecycle events for `tracing`, if the feature is enabled.
+//!
+//! Without it, these functions are empty and optimized away.
+//!
+//! Events of accepted connections belong to a "connection" span,
+//! which [`Listener::accept_with_span`] hands to the caller.
+//! `closed` is only emitted by a successful `poll_shutdown`, not on drop.
+
+use std::io;
+
+use crate::{Addr, Listener, Stream};
+
+/// [`Stream::connect`] finished
+pub(crate) fn connected(peer: &Addr, r: &io::Result<Stream>) {
+    #[cfg(feature = "tracing")]
+    match r {
+        Ok(_) => tracing::debug!(peer = %peer, family = %peer.family(), "connected"),
+        Err(e) => tracing::debug!(peer = %peer, error = %e, "connect failed"),
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (peer, r);
+}
+
+/// The span of an accepted connection, if `tracing` is enabled
+#[cfg(feature = "tracing")]
+pub(crate) type Span = tracing::Span;
+#[cfg(not(feature = "tracing"))]
+pub(crate) type Span = ();
+
+/// A connection was accepted by `l`.
+///
+/// Returns the span of the connection, see [`Listener::accept_with_span`].
+pub(crate) fn accepted(l: &Listener, r: &io::Result<(Stream, Addr)>) -> Span {
+    #[cfg(feature = "tracing")]
+    match r {
+        Ok((_, peer)) => {
+            let span = tracing::debug_span!(
+                "connection",
+                local = %l.local_addr().map_or_else(|e| e.to_string(), |a| a.to_string()),
+                peer = %peer,
+                family = %peer.family(),
+            );
+            span.in_scope(|| tracing::debug!("accepted"));
+            span
+        }
+        Err(_) => tracing::Span::none(),
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (l, r);
+}
+
+/// `s` was shut down.
+///
+/// As there is no async drop, this is only emitted if the stream is shut down successfully.
+/// Streams that are just dropped close silently.
+pub(crate) fn closed(s: &Stream) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        peer = %s.peer_addr().map_or_else(|e| e.to_string(), |a| a.to_string()),
+        "closed"
+    );
+    #[cfg(not(feature = "tracing"))]
+    let _ = s;
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::AsyncWriteExt;
+    use tokio::runtime::Builder;
+    use tracing::field::{Field, Visit};
+    use tracing::Instrument;
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+
+    /// Collects the messages of all events, prefixed by the name of their span
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<String>>>);
+
+    impl<S> tracing_subscriber::Layer<S> for Capture
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+            struct Message(String);
+            impl Visit for Message {
+                fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                    if field.name() == "message" {
+                        self.0 = format!("{:?}", value);
+                    }
+                }
+            }
+            let mut m = Message(String::new());
+            event.record(&mut m);
+            if let Some(span) = ctx.event_span(event) {
+                m.0 = format!("{}: {}", span.name(), m.0);
+            }
+            self.0.lock().unwrap().push(m.0);
+        }
+    }
+
+    #[test]
+    fn events() {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let rt = Builder::new_current_thread().enable_all().build().unwrap();
+            rt.block_on(async {
+                let l = Listener::bind(&"127.0.0.1:0".parse().unwrap()).await.unwrap();
+                let (c, s) = tokio::join!(Stream::connect_to_listener(&l), l.accept_with_span());
+                let mut c = c.unwrap();
+                let (mut s, _, span) = s.unwrap();
+                c.shutdown().await.unwrap();
+                async { s.shutdown().await.unwrap() }.instrument(span).await;
+            });
+        });
+        let events = capture.0.lock().unwrap();
+        assert!(events.iter().any(|e| e == "connected"));
+        assert!(events.iter().any(|e| e == "connection: accepted"));
+        assert!(events.iter().any(|e| e == "closed"));
+        assert!(events.iter().any(|e| e == "connection: closed"));
+    }
+}
@@ -0,0 +1,156 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, Error, ReadBuf};
+use tokio::time::{Instant, Sleep};
+
+use crate::Stream;
+
+/// Fails writes on the wrapped stream that make no progress for a while,
+/// e.g. because the peer stopped reading.
+///
+/// The timer only runs while a write or flush is pending.
+/// If it does not complete within the timeout, it returns an error of kind
+/// [`io::ErrorKind::TimedOut`]. Reads are never timed out,
+/// use [`TimeoutStream`](crate::TimeoutStream) for that.
+/// ```
+/// # use async_stream_connection::Stream;
+/// # use std::time::Duration;
+/// # async fn doc(s: Stream) {
+/// let s = s.with_write_timeout(Duration::from_secs(10));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct WriteTimeoutStream<S> {
+    inner: S,
+    timeout: Duration,
+    sleep: Pin<Box<Sleep>>,
+    /// A write is pending and `sleep` runs for it
+    armed: bool,
+}
+
+impl Stream {
+    /// Wraps this stream, so that a single write fails if it makes no progress within `timeout`.
+    pub fn with_write_timeout(self, timeout: Duration) -> WriteTimeoutStream<Stream> {
+        WriteTimeoutStream::new(self, timeout)
+    }
+}
+
+impl<S> WriteTimeoutStream<S> {
+    /// Wraps `inner`, timing out writes after `timeout`.
+    pub fn new(inner: S, timeout: Duration) -> WriteTimeoutStream<S> {
+        WriteTimeoutStream {
+            inner,
+            timeout,
+            sleep: Box::pin(tokio::time::sleep(timeout)),
+            armed: false,
+        }
+    }
+    /// The write timeout
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+    /// Gets a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+    /// Starts the timer when a write becomes pending, stops it once it completes
+    fn check<T>(&mut self, cx: &mut Context, r: Poll<io::Result<T>>) -> Poll<io::Result<T>> {
+        match r {
+            Poll::Pending => {
+                if !self.armed {
+                    let deadline = Instant::now() + self.timeout;
+                    self.sleep.as_mut().reset(deadline);
+                    self.armed = true;
+                }
+                match self.sleep.as_mut().poll(cx) {
+                    Poll::Ready(()) => {
+                        self.armed = false;
+                        Poll::Ready(Err(io::ErrorKind::TimedOut.into()))
+                    }
+                    Poll::Pending => Poll::Pending,
+                }
+            }
+            r => {
+                self.armed = false;
+                r
+            }
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for WriteTimeoutStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+impl<S: AsyncWrite + Unpin> AsyncWrite for WriteTimeoutStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        let r = Pin::new(&mut self.inner).poll_write(cx, buf);
+        self.check(cx, r)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<Result<usize, Error>> {
+        let r = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+        self.check(cx, r)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        let r = Pin::new(&mut self.inner).poll_flush(cx);
+        self.check(cx, r)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::tests::tcp_pair;
+    use tokio::io::AsyncWriteExt;
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn peer_not_reading() {
+        let rt = Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (c, _s) = tcp_pair().await.unwrap();
+            let mut c = c.with_write_timeout(Duration::from_millis(50));
+            let buf = vec![0u8; 64 * 1024];
+            let e = loop {
+                if let Err(e) = c.write(&buf).await {
+                    break e;
+                }
+            };
+            assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+        });
+    }
+}